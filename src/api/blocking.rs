@@ -0,0 +1,231 @@
+use reqwest::{
+    blocking::Client as ReqwestClient,
+    header::{CONTENT_TYPE, ETAG},
+};
+use std::{fs::File, io, path::Path, time::Instant};
+
+use crate::structs::{
+    client_init_error::ClientInitError,
+    download::DownloadReport,
+    media_error::MediaError,
+    media_request::MediaRequestData,
+    media_response::{self, Response},
+    services::Services,
+    StatusResponse,
+};
+
+use super::{
+    builder::{AuthScheme, ClientBuilder},
+    client,
+    options::RequestOptions,
+    tls,
+};
+
+/// A blocking counterpart of [`api::client::Client`](super::client::Client).
+///
+/// Available with the `blocking` feature, for programs that don't run an async runtime.
+/// The methods mirror the async client and share its configuration through
+/// [`ClientBuilder::build_blocking`].
+///
+/// # Example
+/// ```rust
+/// use cobalt_tools::api::blocking::Client;
+///
+/// let client = Client::builder("your-api-key-here", "http://localhost:9000")
+///     .build_blocking()
+///     .expect("Failed to build client");
+///
+/// match client.status() {
+///     Ok(status) => println!("Service status: {:?}", status),
+///     Err(err) => eprintln!("Error fetching status: {:?}", err),
+/// }
+/// ```
+pub struct Client {
+    pub(crate) api_key: String,
+    pub(crate) instance_uri: String,
+    pub(crate) accept: String,
+    pub(crate) content_type: String,
+    pub(crate) user_agent: String,
+    pub(crate) accept_language: Option<String>,
+    pub(crate) auth_scheme: AuthScheme,
+    pub(crate) query: Vec<(String, String)>,
+    pub(crate) pretty_json: bool,
+    pub(crate) bools_as_strings: bool,
+    pub(crate) offline: bool,
+    pub(crate) request_ids: bool,
+    pub(crate) tls_error: Option<ClientInitError>,
+    pub(crate) http: ReqwestClient,
+}
+
+impl Client {
+    /// Returns a [`ClientBuilder`] for configuring a client explicitly.
+    pub fn builder(api_key: impl Into<String>, instance_uri: impl Into<String>) -> ClientBuilder {
+        ClientBuilder::new(api_key, instance_uri)
+    }
+
+    /// Retrieves the status of the media service.
+    ///
+    /// See [`api::client::Client::status`](super::client::Client::status).
+    pub fn status(&self) -> Result<StatusResponse, Box<dyn std::error::Error + Send + Sync>> {
+        self.check_online("GET", &self.instance_uri)?;
+        tls::check_https(self.tls_error.as_ref(), &self.instance_uri)?;
+        let response = self
+            .http
+            .get(self.instance_uri.clone())
+            .query(&self.query)
+            .send()?
+            .json::<StatusResponse>()?;
+
+        Ok(response)
+    }
+
+    /// Retrieves the list of available services from the media service.
+    ///
+    /// See [`api::client::Client::services`](super::client::Client::services).
+    pub fn services(&self) -> Result<Services, Box<dyn std::error::Error + Send + Sync>> {
+        let status = self.status()?;
+
+        if status.cobalt.services.is_empty() {
+            return Err("No services found".into());
+        }
+
+        Ok(Services::from(status.cobalt.services))
+    }
+
+    /// Fetches media based on the provided request data.
+    ///
+    /// See [`api::client::Client::get_media`](super::client::Client::get_media).
+    pub fn get_media(
+        &self,
+        override_api_key: Option<String>,
+        video_data: MediaRequestData<'_>,
+    ) -> Result<Response, MediaError> {
+        let options = RequestOptions {
+            api_key: override_api_key.as_deref(),
+            ..Default::default()
+        };
+
+        self.get_media_with(options, video_data)
+    }
+
+    /// Fetches media with the client's own API key.
+    ///
+    /// See [`api::client::Client::get_media_default`](super::client::Client::get_media_default).
+    pub fn get_media_default(
+        &self,
+        video_data: MediaRequestData<'_>,
+    ) -> Result<Response, MediaError> {
+        self.get_media_with(RequestOptions::default(), video_data)
+    }
+
+    /// Fetches media like [`Client::get_media`], applying per-call overrides.
+    ///
+    /// See [`api::client::Client::get_media_with`](super::client::Client::get_media_with).
+    pub fn get_media_with(
+        &self,
+        options: RequestOptions<'_>,
+        video_data: MediaRequestData<'_>,
+    ) -> Result<Response, MediaError> {
+        let api_key = options.api_key.unwrap_or(&self.api_key);
+        let accept = options.accept.unwrap_or(&self.accept);
+        let content_type = options.content_type.unwrap_or(&self.content_type);
+
+        video_data.validate()?;
+        tls::check_https(self.tls_error.as_ref(), &self.instance_uri)?;
+
+        let serialized =
+            client::serialize_body(&video_data, self.pretty_json, self.bools_as_strings)?;
+
+        let mut request = self
+            .http
+            .post(self.instance_uri.clone())
+            .query(&self.query)
+            .query(options.query)
+            .header("Content-Type", content_type)
+            .header("Accept", accept)
+            .header("User-Agent", &self.user_agent)
+            .header("Authorization", self.auth_scheme.header_value(api_key))
+            .body(serialized);
+        if let Some(accept_language) = &self.accept_language {
+            request = request.header("Accept-Language", accept_language);
+        }
+        let request_id = match options.request_id {
+            Some(request_id) => Some(request_id.to_string()),
+            None if self.request_ids => Some(client::new_request_id()),
+            None => None,
+        };
+        if let Some(request_id) = request_id {
+            request = request.header("X-Request-Id", request_id);
+        }
+        if self.offline {
+            let request = request.build()?;
+            let body = request.body().and_then(|body| body.as_bytes());
+            let (content_type, body) = client::offline_stub(body.unwrap_or_default());
+            return media_response::parse_body(content_type.as_deref(), &body);
+        }
+        let response = request.send()?;
+
+        if !response.status().is_success() {
+            return Err(MediaError::from_blocking_response(response));
+        }
+
+        let content_type = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        let body = response.bytes()?;
+
+        media_response::parse_body(content_type.as_deref(), &body)
+    }
+
+    /// Downloads a tunnel or redirect URL to `path`.
+    pub fn download(
+        &self,
+        tunnel_link: &str,
+        path: impl AsRef<Path>,
+    ) -> Result<DownloadReport, MediaError> {
+        let start = Instant::now();
+        self.check_online("GET", tunnel_link)?;
+        tls::check_https(self.tls_error.as_ref(), tunnel_link)?;
+        let mut response = self.http.get(tunnel_link).send()?;
+
+        if !response.status().is_success() {
+            return Err(MediaError::from_blocking_response(response));
+        }
+
+        let header = |name| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string)
+        };
+        let content_type = header(CONTENT_TYPE);
+        let etag = header(ETAG);
+
+        let mut file = File::create(path.as_ref())?;
+        let bytes = io::copy(&mut response, &mut file)?;
+
+        Ok(DownloadReport {
+            path: path.as_ref().to_path_buf(),
+            bytes,
+            duration: start.elapsed(),
+            content_type,
+            resumed: false,
+            etag,
+        })
+    }
+
+    /// Fails like the async client if the client is [offline](ClientBuilder::offline),
+    /// since only media requests are answered with a stub.
+    fn check_online(&self, method: &str, url: &str) -> Result<(), MediaError> {
+        match self.offline {
+            true => Err(MediaError::RequestError(format!(
+                "offline client: {} {} was not sent",
+                method, url
+            ))),
+            false => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,586 @@
+use reqwest::{redirect::Policy, Client as ReqwestClient};
+use std::{net::SocketAddr, sync::Arc, time::Duration};
+use tokio::{runtime::Handle, sync::RwLock};
+use tokio_util::sync::CancellationToken;
+
+use crate::structs::{client_init_error::ClientInitError, media_error::MediaError};
+
+use super::{
+    client::Client,
+    clock::{Clock, SystemClock},
+    interceptor::Interceptor,
+    options::CollisionPolicy,
+    retry::RetryConfig,
+    tls,
+};
+
+/// The `Accept` header sent by default with media requests.
+pub const DEFAULT_ACCEPT: &str = "application/json";
+
+/// The `Content-Type` header sent by default with media requests.
+pub const DEFAULT_CONTENT_TYPE: &str = "application/json";
+
+/// The `User-Agent` header sent by default with media requests.
+pub const DEFAULT_USER_AGENT: &str = "Cobalt";
+
+/// How long a fetched instance status is reused by default.
+pub const DEFAULT_STATUS_CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// How often [`Client::download_media`] re-resolves a link to another media page by default.
+pub const DEFAULT_MAX_RESOLUTION_DEPTH: u32 = 3;
+
+/// The host of the official hosted cobalt API.
+pub const OFFICIAL_API_HOST: &str = "api.cobalt.tools";
+
+/// Which HTTP version the client speaks.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum HttpVersion {
+    /// Negotiates HTTP/2 over TLS when the server offers it, falling back to HTTP/1.1.
+    #[default]
+    Auto,
+    /// Only speaks HTTP/1.1, for servers that stall on HTTP/2.
+    Http1Only,
+    /// Speaks HTTP/2 right away, without negotiation.
+    Http2PriorKnowledge,
+}
+
+/// How the API key is presented in the `Authorization` header.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum AuthScheme {
+    /// `Authorization: Api-Key <key>`, for keys configured on the instance.
+    #[default]
+    ApiKey,
+    /// `Authorization: Bearer <token>`, for session tokens issued by the instance.
+    Bearer,
+}
+
+impl AuthScheme {
+    /// Returns the scheme's name as used in the `Authorization` header.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuthScheme::ApiKey => "Api-Key",
+            AuthScheme::Bearer => "Bearer",
+        }
+    }
+
+    /// Formats the `Authorization` header value for `credential`.
+    pub fn header_value(&self, credential: &str) -> String {
+        format!("{} {}", self.as_str(), credential)
+    }
+}
+
+/// A builder for configuring a [`Client`] without relying on environment variables.
+///
+/// # Example
+/// ```rust
+/// use cobalt_tools::api::builder::ClientBuilder;
+///
+/// let client = ClientBuilder::new("your-api-key-here", "http://localhost:9000")
+///     .accept("application/json, text/plain")
+///     .build()
+///     .expect("Failed to build client");
+/// ```
+pub struct ClientBuilder {
+    api_key: String,
+    instance_uri: String,
+    accept: String,
+    content_type: String,
+    user_agent: String,
+    accept_language: Option<String>,
+    timeout: Option<Duration>,
+    status_cache_ttl: Duration,
+    resolve_overrides: Vec<(String, Vec<SocketAddr>)>,
+    redirect_policy: Option<Policy>,
+    http_version: HttpVersion,
+    auth_scheme: AuthScheme,
+    negotiate_auth: bool,
+    query: Vec<(String, String)>,
+    max_resolution_depth: u32,
+    offline: bool,
+    pretty_json: bool,
+    bools_as_strings: bool,
+    collision_policy: CollisionPolicy,
+    retry: RetryConfig,
+    runtime: Option<Handle>,
+    interceptor: Option<Arc<dyn Interceptor>>,
+    clock: Arc<dyn Clock>,
+    #[cfg(feature = "otel")]
+    propagator: Arc<dyn super::otel::TracePropagator>,
+    request_ids: bool,
+    official: Option<bool>,
+    #[cfg(any(feature = "native-tls", feature = "rustls"))]
+    root_certificates: Vec<reqwest::Certificate>,
+}
+
+/// A builder with an empty API key and instance URI, to be set with
+/// [`ClientBuilder::api_key`] and [`ClientBuilder::instance_uri`].
+impl Default for ClientBuilder {
+    fn default() -> Self {
+        ClientBuilder::new(String::new(), String::new())
+    }
+}
+
+impl ClientBuilder {
+    /// Creates a new builder for the given API key and instance URI.
+    pub fn new(api_key: impl Into<String>, instance_uri: impl Into<String>) -> Self {
+        ClientBuilder {
+            api_key: api_key.into(),
+            instance_uri: instance_uri.into(),
+            accept: DEFAULT_ACCEPT.to_string(),
+            content_type: DEFAULT_CONTENT_TYPE.to_string(),
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            accept_language: None,
+            timeout: None,
+            status_cache_ttl: DEFAULT_STATUS_CACHE_TTL,
+            resolve_overrides: Vec::new(),
+            redirect_policy: None,
+            http_version: HttpVersion::Auto,
+            auth_scheme: AuthScheme::ApiKey,
+            negotiate_auth: false,
+            query: Vec::new(),
+            max_resolution_depth: DEFAULT_MAX_RESOLUTION_DEPTH,
+            offline: false,
+            pretty_json: false,
+            bools_as_strings: false,
+            collision_policy: CollisionPolicy::AppendIndex,
+            retry: RetryConfig::default(),
+            runtime: None,
+            interceptor: None,
+            clock: Arc::new(SystemClock),
+            #[cfg(feature = "otel")]
+            propagator: Arc::new(super::otel::W3CTraceContext),
+            request_ids: false,
+            official: None,
+            #[cfg(any(feature = "native-tls", feature = "rustls"))]
+            root_certificates: Vec::new(),
+        }
+    }
+
+    /// Sets the API key sent with media requests.
+    pub fn api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = api_key.into();
+        self
+    }
+
+    /// Sets the URI of the cobalt instance.
+    pub fn instance_uri(mut self, instance_uri: impl Into<String>) -> Self {
+        self.instance_uri = instance_uri.into();
+        self
+    }
+
+    /// Sets the default `Accept` header used by `get_media`.
+    pub fn accept(mut self, accept: impl Into<String>) -> Self {
+        self.accept = accept.into();
+        self
+    }
+
+    /// Sets the default `Content-Type` header used by `get_media`.
+    pub fn content_type(mut self, content_type: impl Into<String>) -> Self {
+        self.content_type = content_type.into();
+        self
+    }
+
+    /// Sets the `User-Agent` header used by `get_media`.
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = user_agent.into();
+        self
+    }
+
+    /// Sets the `Accept-Language` header used by `get_media`, e.g. `"de-DE, en;q=0.8"`.
+    ///
+    /// Some services localize titles and filenames based on it. Not sent by default.
+    pub fn accept_language(mut self, accept_language: impl Into<String>) -> Self {
+        self.accept_language = Some(accept_language.into());
+        self
+    }
+
+    /// Sets a total timeout applied to every request, from connecting until the body is read.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Sets how long [`Client::cached_status`] reuses a fetched status before refetching.
+    pub fn status_cache_ttl(mut self, ttl: Duration) -> Self {
+        self.status_cache_ttl = ttl;
+        self
+    }
+
+    /// Resolves `domain` to `addr` instead of using DNS.
+    ///
+    /// The URL keeps its hostname, so the `Host` header and TLS SNI still use `domain`.
+    /// If `addr` has port 0, the port of the URL is used.
+    pub fn resolve(self, domain: impl Into<String>, addr: SocketAddr) -> Self {
+        self.resolve_to_addrs(domain, &[addr])
+    }
+
+    /// Resolves `domain` to the given addresses instead of using DNS.
+    ///
+    /// See [`ClientBuilder::resolve`].
+    pub fn resolve_to_addrs(mut self, domain: impl Into<String>, addrs: &[SocketAddr]) -> Self {
+        self.resolve_overrides.push((domain.into(), addrs.to_vec()));
+        self
+    }
+
+    /// Sets how redirects are followed, e.g. `Policy::limited(1)` or `Policy::none()`.
+    ///
+    /// The policy applies to every request made through the client, including downloads
+    /// of tunnel and redirect URLs. Defaults to reqwest's policy of following up to
+    /// 10 redirects.
+    pub fn redirect_policy(mut self, policy: Policy) -> Self {
+        self.redirect_policy = Some(policy);
+        self
+    }
+
+    /// Sets the HTTP version used for every request, including downloads.
+    ///
+    /// Some tunnel servers hang on HTTP/2; [`HttpVersion::Http1Only`] avoids that.
+    /// Defaults to [`HttpVersion::Auto`].
+    pub fn http_version(mut self, version: HttpVersion) -> Self {
+        self.http_version = version;
+        self
+    }
+
+    /// Sets the scheme of the `Authorization` header sent with media requests.
+    ///
+    /// Defaults to [`AuthScheme::ApiKey`].
+    pub fn auth_scheme(mut self, scheme: AuthScheme) -> Self {
+        self.auth_scheme = scheme;
+        self
+    }
+
+    /// Picks the `Authorization` scheme from the instance's cached status instead of
+    /// always using the one set with [`ClientBuilder::auth_scheme`].
+    ///
+    /// See [`Cobalt::auth_scheme`](crate::structs::status::Cobalt::auth_scheme) for how
+    /// the scheme is chosen. The configured scheme is used when the status is ambiguous
+    /// or can't be fetched. Off by default; the blocking client doesn't negotiate.
+    pub fn negotiate_auth(mut self, enabled: bool) -> Self {
+        self.negotiate_auth = enabled;
+        self
+    }
+
+    /// Appends a query parameter to the instance URI of every status and media request.
+    ///
+    /// For nonstandard deployments that expect options in the query string. Nothing is
+    /// added by default. Per-call parameters can be set with
+    /// [`RequestOptions::query`](super::options::RequestOptions::query).
+    pub fn query_param(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.query.push((key.into(), value.into()));
+        self
+    }
+
+    /// Sets how often [`Client::download_media`] re-resolves a returned link that points
+    /// to another media page rather than a file, before failing with
+    /// `MediaError::TooManyRedirects`.
+    ///
+    /// Defaults to [`DEFAULT_MAX_RESOLUTION_DEPTH`]; 0 never re-resolves.
+    pub fn max_resolution_depth(mut self, depth: u32) -> Self {
+        self.max_resolution_depth = depth;
+        self
+    }
+
+    /// Builds a client that never touches the network, for testing request construction.
+    ///
+    /// Media requests are validated, serialized and passed to the [`Interceptor`] as
+    /// usual, but instead of being sent they get a stub redirect response. Its URL and
+    /// file name are derived from a hash of the request body, so identical requests get
+    /// identical responses. Every other request, e.g. for the status or a download, fails
+    /// with `MediaError::RequestError`.
+    ///
+    /// Off by default.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cobalt_tools::api::builder::ClientBuilder;
+    /// use cobalt_tools::structs::media_request::MediaRequestData;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = ClientBuilder::new("your-api-key-here", "http://localhost:9000")
+    ///         .offline(true)
+    ///         .build()
+    ///         .expect("Failed to build client");
+    ///
+    ///     let video_data = MediaRequestData {
+    ///         url: "https://www.youtube.com/watch?v=1lML-Uem6Ns",
+    ///         ..Default::default()
+    ///     };
+    ///
+    ///     let response = client.get_media(None, video_data).await.unwrap();
+    ///     assert!(response.is_single_file());
+    /// }
+    /// ```
+    pub fn offline(mut self, enabled: bool) -> Self {
+        self.offline = enabled;
+        self
+    }
+
+    /// Sends media request bodies as pretty-printed JSON, which is easier to read in
+    /// proxy logs while troubleshooting.
+    ///
+    /// Off by default, which sends compact JSON.
+    pub fn pretty_json(mut self, enabled: bool) -> Self {
+        self.pretty_json = enabled;
+        self
+    }
+
+    /// Sends the boolean options of media requests, e.g. `alwaysProxy`, as the strings
+    /// `"true"` and `"false"`, for older or forked instances that expect them.
+    ///
+    /// Off by default, which sends JSON booleans.
+    pub fn bools_as_strings(mut self, enabled: bool) -> Self {
+        self.bools_as_strings = enabled;
+        self
+    }
+
+    /// Sets what the helpers that download into a directory, such as
+    /// [`Client::download_media`] and [`Client::download_picker`], do when a file they
+    /// would write already exists.
+    ///
+    /// Defaults to [`CollisionPolicy::AppendIndex`], so no file is overwritten.
+    pub fn collision_policy(mut self, policy: CollisionPolicy) -> Self {
+        self.collision_policy = policy;
+        self
+    }
+
+    /// Sets how [`Client::get_media_retrying`] and [`Client::download_retrying`] retry
+    /// failures. Defaults to [`RetryConfig::default`].
+    pub fn retry(mut self, config: RetryConfig) -> Self {
+        self.retry = config;
+        self
+    }
+
+    /// Sets the tokio runtime that [`Client::spawn`] and the concurrent helpers spawn
+    /// their tasks on.
+    ///
+    /// Defaults to the runtime the client is used from, which must then be a tokio
+    /// runtime. Setting a handle lets the client be driven from other executors.
+    pub fn runtime_handle(mut self, handle: Handle) -> Self {
+        self.runtime = Some(handle);
+        self
+    }
+
+    /// Sets an [`Interceptor`] that observes every request and response of the client.
+    pub fn interceptor(mut self, interceptor: Arc<dyn Interceptor>) -> Self {
+        self.interceptor = Some(interceptor);
+        self
+    }
+
+    /// Sets the [`Clock`] the client reads time from, e.g. a
+    /// [`MockClock`](super::clock::MockClock) in tests.
+    ///
+    /// Defaults to [`SystemClock`].
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Sets the [`TracePropagator`](super::otel::TracePropagator) that writes trace
+    /// context, such as `traceparent`, into every request.
+    ///
+    /// Defaults to [`W3CTraceContext`](super::otel::W3CTraceContext).
+    #[cfg(feature = "otel")]
+    pub fn propagator(mut self, propagator: Arc<dyn super::otel::TracePropagator>) -> Self {
+        self.propagator = propagator;
+        self
+    }
+
+    /// Generates a random `X-Request-Id` for every media request that doesn't carry one
+    /// in [`RequestOptions::request_id`](super::options::RequestOptions::request_id).
+    ///
+    /// Off by default.
+    pub fn request_ids(mut self, enabled: bool) -> Self {
+        self.request_ids = enabled;
+        self
+    }
+
+    /// Sets whether the instance is treated as the official cobalt API, instead of
+    /// deciding by whether the instance URI's host is [`OFFICIAL_API_HOST`].
+    ///
+    /// The official API only serves authenticated requests, so building a client for it
+    /// without an API key fails. Useful for a proxy in front of the official API, or to
+    /// lift the requirement for a self-hosted instance that shares the host name.
+    pub fn official(mut self, official: bool) -> Self {
+        self.official = Some(official);
+        self
+    }
+
+    /// Returns whether the client is built for the official cobalt API, failing if it is
+    /// and no API key is set.
+    fn check_official(&self) -> Result<bool, MediaError> {
+        let official = self
+            .official
+            .unwrap_or_else(|| is_official_uri(&self.instance_uri));
+
+        if official && self.api_key.is_empty() {
+            return Err(MediaError::InvalidRequest(
+                "the official cobalt API requires an API key".to_string(),
+            ));
+        }
+
+        Ok(official)
+    }
+
+    /// Returns why HTTPS requests of the built client would fail, see
+    /// [`tls::missing_roots`].
+    fn tls_error(&self) -> Option<ClientInitError> {
+        #[cfg(feature = "rustls")]
+        {
+            tls::missing_roots(!self.root_certificates.is_empty())
+        }
+
+        #[cfg(not(feature = "rustls"))]
+        {
+            None
+        }
+    }
+
+    /// Trusts an additional root certificate, e.g. for an instance behind a private CA.
+    #[cfg(any(feature = "native-tls", feature = "rustls"))]
+    pub fn root_certificate(mut self, certificate: reqwest::Certificate) -> Self {
+        self.root_certificates.push(certificate);
+        self
+    }
+
+    /// Builds the configured [`Client`].
+    ///
+    /// The underlying HTTP client is created once here and shared by every request,
+    /// using the TLS backend selected by the `native-tls` or `rustls` feature.
+    ///
+    /// With the `rustls` feature, the client is built even if no system root certificates
+    /// are found, but then its HTTPS requests fail with `MediaError::ClientInit` unless
+    /// roots were added with [`ClientBuilder::root_certificate`].
+    ///
+    /// # Errors
+    /// Returns `MediaError::RequestError` if the HTTP client cannot be initialized, or
+    /// `MediaError::InvalidRequest` if the instance is the
+    /// [official API](ClientBuilder::official) and no API key is set.
+    pub fn build(self) -> Result<Client, MediaError> {
+        let official = self.check_official()?;
+        let tls_error = self.tls_error();
+        let mut http = tls::configure(ReqwestClient::builder());
+
+        if let Some(timeout) = self.timeout {
+            http = http.timeout(timeout);
+        }
+
+        for (domain, addrs) in &self.resolve_overrides {
+            http = http.resolve_to_addrs(domain, addrs);
+        }
+
+        if let Some(policy) = self.redirect_policy {
+            http = http.redirect(policy);
+        }
+
+        match self.http_version {
+            HttpVersion::Auto => {}
+            HttpVersion::Http1Only => http = http.http1_only(),
+            HttpVersion::Http2PriorKnowledge => http = http.http2_prior_knowledge(),
+        }
+
+        #[cfg(any(feature = "native-tls", feature = "rustls"))]
+        for certificate in self.root_certificates {
+            http = http.add_root_certificate(certificate);
+        }
+
+        Ok(Client {
+            api_key: self.api_key,
+            instance_uri: self.instance_uri,
+            accept: self.accept,
+            content_type: self.content_type,
+            user_agent: self.user_agent,
+            accept_language: self.accept_language,
+            http: http.build()?,
+            status_cache: Arc::new(RwLock::new(None)),
+            status_cache_ttl: self.status_cache_ttl,
+            auth_scheme: self.auth_scheme,
+            negotiate_auth: self.negotiate_auth,
+            query: self.query,
+            max_resolution_depth: self.max_resolution_depth,
+            offline: self.offline,
+            pretty_json: self.pretty_json,
+            bools_as_strings: self.bools_as_strings,
+            collision_policy: self.collision_policy,
+            retry: self.retry,
+            runtime: self.runtime,
+            interceptor: self.interceptor,
+            clock: self.clock,
+            #[cfg(feature = "otel")]
+            propagator: self.propagator,
+            request_ids: self.request_ids,
+            official,
+            tls_error,
+            cancel: CancellationToken::new(),
+        })
+    }
+
+    /// Builds a [`blocking::Client`](super::blocking::Client) with the same configuration.
+    ///
+    /// Like reqwest's blocking client, it must not be built or used from within an async
+    /// runtime.
+    ///
+    /// [`ClientBuilder::offline`] and [`ClientBuilder::request_ids`] apply as to the async
+    /// client. The interceptor, auth negotiation, retry settings, and clock only apply to
+    /// the async client.
+    ///
+    /// With the `rustls` feature, the client is built even if no system root certificates
+    /// are found, but then its HTTPS requests fail with `MediaError::ClientInit` unless
+    /// roots were added with [`ClientBuilder::root_certificate`].
+    ///
+    /// # Errors
+    /// Returns `MediaError::RequestError` if the HTTP client cannot be initialized, or
+    /// `MediaError::InvalidRequest` if the instance is the
+    /// [official API](ClientBuilder::official) and no API key is set.
+    #[cfg(feature = "blocking")]
+    pub fn build_blocking(self) -> Result<super::blocking::Client, MediaError> {
+        self.check_official()?;
+        let tls_error = self.tls_error();
+        let mut http = tls::configure_blocking(reqwest::blocking::Client::builder());
+
+        if let Some(timeout) = self.timeout {
+            http = http.timeout(timeout);
+        }
+
+        for (domain, addrs) in &self.resolve_overrides {
+            http = http.resolve_to_addrs(domain, addrs);
+        }
+
+        if let Some(policy) = self.redirect_policy {
+            http = http.redirect(policy);
+        }
+
+        match self.http_version {
+            HttpVersion::Auto => {}
+            HttpVersion::Http1Only => http = http.http1_only(),
+            HttpVersion::Http2PriorKnowledge => http = http.http2_prior_knowledge(),
+        }
+
+        #[cfg(any(feature = "native-tls", feature = "rustls"))]
+        for certificate in self.root_certificates {
+            http = http.add_root_certificate(certificate);
+        }
+
+        Ok(super::blocking::Client {
+            api_key: self.api_key,
+            instance_uri: self.instance_uri,
+            accept: self.accept,
+            content_type: self.content_type,
+            user_agent: self.user_agent,
+            accept_language: self.accept_language,
+            auth_scheme: self.auth_scheme,
+            query: self.query,
+            pretty_json: self.pretty_json,
+            bools_as_strings: self.bools_as_strings,
+            offline: self.offline,
+            request_ids: self.request_ids,
+            tls_error,
+            http: http.build()?,
+        })
+    }
+}
+
+/// Returns `true` if the host of `instance_uri` is [`OFFICIAL_API_HOST`].
+pub(crate) fn is_official_uri(instance_uri: &str) -> bool {
+    reqwest::Url::parse(instance_uri).is_ok_and(|url| url.host_str() == Some(OFFICIAL_API_HOST))
+}
@@ -1,36 +1,275 @@
-use futures_util::StreamExt;
+use bytes::Bytes;
+use futures_util::{stream::BoxStream, StreamExt};
 use once_cell::sync::Lazy;
-use reqwest::Client as ReqwestClient;
-use std::{env, fs::File, io::Write, sync::Arc};
-use tokio::sync::RwLock;
+use reqwest::{
+    header::{CONTENT_TYPE, ETAG},
+    Client as ReqwestClient, Method, RequestBuilder,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    collections::{hash_map::RandomState, HashSet},
+    env,
+    fs::File,
+    future::Future,
+    hash::{BuildHasher, Hasher},
+    io::Write,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::{runtime::Handle, sync::RwLock, task::JoinHandle};
+use tokio_util::sync::CancellationToken;
 
 use crate::structs::{
-    media_error::MediaError, media_request::MediaRequestData, media_response::Response,
+    client_init_error::ClientInitError,
+    download::DownloadReport,
+    health::{CheckOutcome, HealthReport},
+    media_error::MediaError,
+    media_request::{fnv1a, MediaRequestData, VideoQuality},
+    media_response::{self, Response},
+    services::Services,
+    stats::RequestStats,
+    status::ServicesOnly,
     StatusResponse,
 };
 
+use super::{
+    builder::{AuthScheme, ClientBuilder},
+    clock::Clock,
+    config::CobaltConfig,
+    interceptor::Interceptor,
+    options::{CollisionPolicy, RequestOptions},
+    retry::RetryConfig,
+    tls,
+};
+
 /// A client for interacting with the media service.
+///
+/// Cloning is cheap: clones share the HTTP connection pool and the status cache used by
+/// [`Client::cached_status`], so a status fetched through one handle is reused by all.
+/// They also share the shutdown state, so [`Client::shutdown`] on any clone stops the
+/// operations of all of them.
+#[derive(Clone)]
 pub struct Client {
-    api_key: String,
-    instance_uri: String,
+    pub(crate) api_key: String,
+    pub(crate) instance_uri: String,
+    pub(crate) accept: String,
+    pub(crate) content_type: String,
+    pub(crate) user_agent: String,
+    pub(crate) accept_language: Option<String>,
+    pub(crate) http: ReqwestClient,
+    pub(crate) status_cache: Arc<RwLock<Option<(Instant, StatusResponse)>>>,
+    pub(crate) status_cache_ttl: Duration,
+    pub(crate) auth_scheme: AuthScheme,
+    pub(crate) negotiate_auth: bool,
+    pub(crate) query: Vec<(String, String)>,
+    pub(crate) max_resolution_depth: u32,
+    pub(crate) offline: bool,
+    pub(crate) pretty_json: bool,
+    pub(crate) bools_as_strings: bool,
+    pub(crate) collision_policy: CollisionPolicy,
+    pub(crate) retry: RetryConfig,
+    pub(crate) runtime: Option<Handle>,
+    pub(crate) interceptor: Option<Arc<dyn Interceptor>>,
+    pub(crate) clock: Arc<dyn Clock>,
+    #[cfg(feature = "otel")]
+    pub(crate) propagator: Arc<dyn super::otel::TracePropagator>,
+    pub(crate) request_ids: bool,
+    pub(crate) official: bool,
+    pub(crate) tls_error: Option<ClientInitError>,
+    pub(crate) cancel: CancellationToken,
 }
 
 impl Client {
-    /// Creates a new instance of the client.
+    /// Creates the client behind [`CobaltClient`](super::CobaltClient).
     ///
     /// # Panics
-    /// Panics if `API_KEY` or `INSTANCE_URI` are not set in the environment.
+    /// Panics with the [`ClientInitError`] if [`Client::from_env`] fails, since the lazily
+    /// initialized global has no way to return it. Use `from_env` directly to handle it.
     pub(crate) fn new() -> Self {
-        let api_key = env::var("API_KEY").expect("Expected API_KEY in the environment");
-        let instance_uri =
-            env::var("INSTANCE_URI").expect("Expected INSTANCE_URI in the environment");
+        Self::from_env().unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Creates a client from the `API_KEY` and `INSTANCE_URI` environment variables.
+    ///
+    /// # Errors
+    /// Returns the errors of [`Client::validate_env`], or `ClientInitError::HttpClient`
+    /// if the HTTP client cannot be initialized.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cobalt_tools::api::client::Client;
+    ///
+    /// std::env::set_var("API_KEY", "your-api-key-here");
+    /// std::env::set_var("INSTANCE_URI", "http://localhost:9000");
+    ///
+    /// let client = Client::from_env().expect("Invalid configuration");
+    /// ```
+    pub fn from_env() -> Result<Self, ClientInitError> {
+        let (api_key, instance_uri) = env_config()?;
+
+        ClientBuilder::new(api_key, instance_uri)
+            .build()
+            .map_err(ClientInitError::from_build_error)
+    }
+
+    /// Checks that `API_KEY` and `INSTANCE_URI` are set and that the URI is usable,
+    /// without making a request.
+    ///
+    /// Call this at startup to fail with a clear error instead of a panic on the first
+    /// use of [`CobaltClient`](super::CobaltClient).
+    ///
+    /// # Example
+    /// ```rust
+    /// use cobalt_tools::api::client::Client;
+    ///
+    /// std::env::set_var("API_KEY", "your-api-key-here");
+    /// std::env::set_var("INSTANCE_URI", "http://localhost:9000");
+    ///
+    /// Client::validate_env().expect("Invalid configuration");
+    /// ```
+    pub fn validate_env() -> Result<(), ClientInitError> {
+        env_config().map(|_| ())
+    }
+
+    /// Returns a [`ClientBuilder`] for configuring a client explicitly.
+    pub fn builder(api_key: impl Into<String>, instance_uri: impl Into<String>) -> ClientBuilder {
+        ClientBuilder::new(api_key, instance_uri)
+    }
 
-        Client {
-            api_key,
-            instance_uri,
+    /// Creates a client from an already loaded [`CobaltConfig`].
+    ///
+    /// # Errors
+    /// Returns `MediaError::RequestError` if the HTTP client cannot be initialized.
+    pub fn from_config(config: CobaltConfig) -> Result<Self, MediaError> {
+        config.into_builder().build()
+    }
+
+    /// Returns the URI of the instance the client sends requests to.
+    pub fn instance_uri(&self) -> &str {
+        &self.instance_uri
+    }
+
+    /// Returns `true` if the client talks to the official cobalt API rather than a
+    /// self-hosted instance.
+    ///
+    /// Decided by the instance URI's host unless set with [`ClientBuilder::official`].
+    ///
+    /// # Example
+    /// ```rust
+    /// use cobalt_tools::api::client::Client;
+    ///
+    /// let client = Client::builder("your-api-key-here", "https://api.cobalt.tools")
+    ///     .build()
+    ///     .expect("Failed to build client");
+    /// assert!(client.is_official());
+    /// ```
+    pub fn is_official(&self) -> bool {
+        self.official
+    }
+
+    /// Returns the API key for display, with all but its first and last 4 characters
+    /// masked.
+    ///
+    /// Keys shorter than 12 characters are masked completely, and the mask has a fixed
+    /// length, so neither the full key nor its length is revealed.
+    pub fn masked_api_key(&self) -> String {
+        mask_secret(&self.api_key)
+    }
+
+    /// Spawns `future` on the runtime set with [`ClientBuilder::runtime_handle`], or on
+    /// the current tokio runtime if none was set.
+    ///
+    /// # Panics
+    /// Panics if no handle was set and this is called outside of a tokio runtime.
+    pub fn spawn<F>(&self, future: F) -> JoinHandle<F::Output>
+    where
+        F: std::future::Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        match &self.runtime {
+            Some(handle) => handle.spawn(future),
+            None => tokio::spawn(future),
         }
     }
 
+    /// Cancels every in-flight operation of this client and its clones, and makes every
+    /// later one fail.
+    ///
+    /// Requests waiting for a response are dropped, and downloads stop at the next
+    /// chunk, so they fail with [`MediaError::Cancelled`]. Bytes already written stay
+    /// where they are: a plain download leaves a partial file that can be resumed with
+    /// another client, while an [atomic](super::options::DownloadOptions::atomic) one removes its
+    /// partial file. Streams end with a `Cancelled` item after the chunks they already
+    /// yielded.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cobalt_tools::api::client::Client;
+    /// use cobalt_tools::structs::media_error::MediaError;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = Client::builder("your-api-key-here", "http://localhost:9000")
+    ///         .build()
+    ///         .expect("Failed to build client");
+    ///
+    ///     client.shutdown();
+    ///     assert!(client.is_shut_down());
+    ///     assert!(matches!(client.services_only().await, Err(MediaError::Cancelled)));
+    /// }
+    /// ```
+    pub fn shutdown(&self) {
+        self.cancel.cancel();
+    }
+
+    /// Returns `true` once [`Client::shutdown`] was called on this client or a clone.
+    pub fn is_shut_down(&self) -> bool {
+        self.cancel.is_cancelled()
+    }
+
+    /// Returns a token that is cancelled when the client shuts down, to stop work of
+    /// your own along with the client's.
+    ///
+    /// The token is a child token: cancelling it doesn't shut the client down.
+    pub fn cancellation_token(&self) -> CancellationToken {
+        self.cancel.child_token()
+    }
+
+    /// Runs `future` until it completes or the client shuts down.
+    pub(crate) async fn until_shutdown<F: Future>(
+        &self,
+        future: F,
+    ) -> Result<F::Output, MediaError> {
+        self.cancel
+            .run_until_cancelled(future)
+            .await
+            .ok_or(MediaError::Cancelled)
+    }
+
+    /// Returns the body of `response` as a stream that ends with
+    /// [`MediaError::Cancelled`] if the client shuts down before the body is complete.
+    pub(crate) fn body_stream(
+        &self,
+        response: reqwest::Response,
+    ) -> BoxStream<'static, Result<Bytes, MediaError>> {
+        let body = response
+            .bytes_stream()
+            .map(|chunk| chunk.map_err(MediaError::from));
+        let cancel = self.cancel.clone();
+        futures_util::stream::unfold(Some(body), move |body| {
+            let cancel = cancel.clone();
+            async move {
+                let mut body = body?;
+                match cancel.run_until_cancelled(body.next()).await {
+                    Some(chunk) => chunk.map(|chunk| (chunk, Some(body))),
+                    None => Some((Err(MediaError::Cancelled), None)),
+                }
+            }
+        })
+        .boxed()
+    }
+
     /// Retrieves the status of the media service.
     ///
     /// # Returns
@@ -57,7 +296,8 @@ impl Client {
     /// }
     /// ```
     pub async fn status(&self) -> Result<StatusResponse, Box<dyn std::error::Error + Send + Sync>> {
-        let response = reqwest::get(self.instance_uri.clone())
+        let response = self
+            .execute(self.http.get(self.instance_uri.clone()).query(&self.query))
             .await?
             .json::<StatusResponse>()
             .await?;
@@ -77,7 +317,7 @@ impl Client {
     /// - The `services` list in the `StatusResponse` is empty.
     ///
     /// # Returns
-    /// - `Ok(Vec<String>)`: A vector containing the names of available services if the request succeeds.
+    /// - `Ok(Services)`: The names of available services if the request succeeds.
     /// - `Err(Box<dyn std::error::Error + Send + Sync>)`: An error if the request fails or no services are found.
     ///
     /// # Examples
@@ -87,6 +327,10 @@ impl Client {
     ///
     /// #[tokio::main]
     /// async fn main() {
+    ///     // Ensure API_KEY and INSTANCE_URI environment variables are set.
+    ///     std::env::set_var("API_KEY", "your-api-key-here");
+    ///     std::env::set_var("INSTANCE_URI", "http://localhost:9000");
+    ///
     ///     // Set up the client instance
     ///     let client = CobaltClient.read().await;
     ///
@@ -101,14 +345,280 @@ impl Client {
     ///     }
     /// }
     /// ```
-    pub async fn services(&self) -> Result<Vec<String>, Box<dyn std::error::Error + Send + Sync>> {
+    pub async fn services(&self) -> Result<Services, Box<dyn std::error::Error + Send + Sync>> {
         let status = self.status().await?;
 
         if status.cobalt.services.is_empty() {
             return Err("No services found".into());
         }
 
-        Ok(status.cobalt.services)
+        Ok(Services::from(status.cobalt.services))
+    }
+
+    /// Fetches only the instance's service list, without building a full
+    /// [`StatusResponse`].
+    ///
+    /// Only `cobalt.services` is kept while parsing the status, which makes this cheaper
+    /// than [`Client::services`] on hot paths. Unlike `services`, an empty list is
+    /// returned as is.
+    ///
+    /// # Errors
+    /// Returns `MediaError::Api` for an error status, `MediaError::DeserializationError`
+    /// if the body has no service list, or `MediaError::RequestError` if the instance
+    /// can't be reached.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cobalt_tools::api::client::Client;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = Client::builder("your-api-key-here", "http://localhost:9000")
+    ///         .build()
+    ///         .expect("Failed to build client");
+    ///
+    ///     match client.services_only().await {
+    ///         Ok(services) => println!("youtube enabled: {}", services.contains("youtube")),
+    ///         Err(err) => eprintln!("{}", err),
+    ///     }
+    /// }
+    /// ```
+    pub async fn services_only(&self) -> Result<Services, MediaError> {
+        let response = self
+            .execute(self.http.get(self.instance_uri.clone()).query(&self.query))
+            .await?;
+        if !response.status().is_success() {
+            return Err(MediaError::from_response(response).await);
+        }
+
+        let body = response.bytes().await?;
+        let status: ServicesOnly = serde_json::from_slice(&body)?;
+
+        Ok(Services::from(status.cobalt.services))
+    }
+
+    /// Checks that the configured instance URI points to a cobalt instance.
+    ///
+    /// This behaves like [`Client::status`], but a response that cannot be parsed as a
+    /// cobalt status is reported as `MediaError::InvalidInstance`, including the HTTP
+    /// status the server answered with, instead of a bare deserialization error.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cobalt_tools::api::client::Client;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = Client::builder("your-api-key-here", "http://localhost:9000")
+    ///         .build()
+    ///         .expect("Failed to build client");
+    ///
+    ///     if let Err(err) = client.verify_instance().await {
+    ///         eprintln!("{}", err);
+    ///     }
+    /// }
+    /// ```
+    pub async fn verify_instance(&self) -> Result<StatusResponse, MediaError> {
+        let response = self
+            .execute(self.http.get(self.instance_uri.clone()).query(&self.query))
+            .await?;
+        let status = response.status();
+        let body = response.text().await?;
+
+        serde_json::from_str::<StatusResponse>(&body).map_err(|error| {
+            MediaError::InvalidInstance(format!(
+                "{} doesn't look like a cobalt instance (HTTP {}): {}",
+                self.instance_uri, status, error
+            ))
+        })
+    }
+
+    /// Opens a connection to the instance with a `HEAD` request, so the first media
+    /// request doesn't pay for the TCP and TLS handshakes.
+    ///
+    /// The connection stays in the pool shared by every clone of the client until it has
+    /// been idle for too long, so call this shortly before requests are expected, e.g.
+    /// at server startup. The instance's answer is ignored.
+    ///
+    /// # Errors
+    /// Returns `MediaError::RequestError` if the instance can't be reached.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cobalt_tools::api::client::Client;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = Client::builder("your-api-key-here", "http://localhost:9000")
+    ///         .build()
+    ///         .expect("Failed to build client");
+    ///
+    ///     if let Err(err) = client.warm_up().await {
+    ///         eprintln!("Instance unreachable: {}", err);
+    ///     }
+    /// }
+    /// ```
+    pub async fn warm_up(&self) -> Result<(), MediaError> {
+        self.execute(self.http.head(self.instance_uri.clone()).query(&self.query))
+            .await?;
+
+        Ok(())
+    }
+
+    /// Starts a request to `path` on the instance, for endpoints this crate doesn't model.
+    ///
+    /// The request goes through the client's shared connection pool and already carries
+    /// the `Authorization`, `User-Agent` and `Accept-Language` headers and the query
+    /// parameters set on the builder. `Authorization` uses the configured
+    /// [`AuthScheme`] without negotiation.
+    ///
+    /// The request is sent as is: the interceptor doesn't see it, no retries are made,
+    /// and parsing the response, including error statuses, is the caller's
+    /// responsibility.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cobalt_tools::api::client::Client;
+    /// use reqwest::Method;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = Client::builder("your-api-key-here", "http://localhost:9000")
+    ///         .build()
+    ///         .expect("Failed to build client");
+    ///
+    ///     match client.request(Method::POST, "/session").send().await {
+    ///         Ok(response) => println!("Session endpoint answered {}", response.status()),
+    ///         Err(err) => eprintln!("Request failed: {}", err),
+    ///     }
+    /// }
+    /// ```
+    pub fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        let url = format!(
+            "{}/{}",
+            self.instance_uri.trim_end_matches('/'),
+            path.trim_start_matches('/')
+        );
+
+        let mut request = self
+            .http
+            .request(method, url)
+            .query(&self.query)
+            .header("User-Agent", &self.user_agent)
+            .header(
+                "Authorization",
+                self.auth_scheme.header_value(&self.api_key),
+            );
+        if let Some(accept_language) = &self.accept_language {
+            request = request.header("Accept-Language", accept_language);
+        }
+
+        request
+    }
+
+    /// Checks that the client is set up correctly: the instance is reachable, answers as
+    /// a cobalt instance, and accepts the API key.
+    ///
+    /// The key is checked with a media request for a URL no service handles, which
+    /// cobalt rejects without contacting any service. Any answer other than an
+    /// authentication error means the key was accepted. The check is skipped if the
+    /// status check fails.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cobalt_tools::api::client::Client;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = Client::builder("your-api-key-here", "http://localhost:9000")
+    ///         .build()
+    ///         .expect("Failed to build client");
+    ///
+    ///     let report = client.healthcheck().await;
+    ///     if !report.is_healthy() {
+    ///         eprintln!("Instance check: {:?}", report.instance);
+    ///         eprintln!("API key check: {:?}", report.auth);
+    ///     }
+    /// }
+    /// ```
+    pub async fn healthcheck(&self) -> HealthReport {
+        let start = self.clock.now();
+        let status = self.verify_instance().await;
+        let status_latency = self.clock.now().saturating_duration_since(start);
+
+        let version = match status {
+            Ok(status) => status.cobalt.version,
+            Err(error) => {
+                return HealthReport {
+                    status_latency,
+                    instance: CheckOutcome::Failed(error.to_string()),
+                    version: None,
+                    auth: CheckOutcome::Skipped,
+                };
+            }
+        };
+
+        let sentinel = MediaRequestData {
+            url: HEALTHCHECK_URL,
+            ..Default::default()
+        };
+        let auth = match self
+            .get_media(None, sentinel)
+            .await
+            .and_then(Response::into_media_result)
+        {
+            Ok(_) => CheckOutcome::Passed,
+            Err(error) => match error.error_response() {
+                Some(response) => auth_check(response.error.code),
+                None => CheckOutcome::Failed(error.to_string()),
+            },
+        };
+
+        HealthReport {
+            status_latency,
+            instance: CheckOutcome::Passed,
+            version: Some(version),
+            auth,
+        }
+    }
+
+    /// Returns the instance status, reusing a previous result while it is fresh.
+    ///
+    /// A status is kept for the TTL configured with [`ClientBuilder::status_cache_ttl`]
+    /// (60 seconds by default). The cache is shared by every clone of this client, and
+    /// only successful fetches are cached.
+    ///
+    /// # Errors
+    /// Fails like [`Client::verify_instance`] when a fresh status has to be fetched.
+    pub async fn cached_status(&self) -> Result<StatusResponse, MediaError> {
+        if let Some((fetched_at, status)) = self.status_cache.read().await.as_ref() {
+            if self.clock.now().saturating_duration_since(*fetched_at) < self.status_cache_ttl {
+                return Ok(status.clone());
+            }
+        }
+
+        let status = self.verify_instance().await?;
+        *self.status_cache.write().await = Some((self.clock.now(), status.clone()));
+
+        Ok(status)
+    }
+
+    /// Checks a known media duration against the instance's duration limit before
+    /// requesting it, using the cached status.
+    ///
+    /// Cobalt rejects media over its limit only after resolving it, so checking up front
+    /// gives a clearer and earlier error when the duration is known.
+    ///
+    /// # Errors
+    /// Returns `MediaError::TooLong` if `duration` exceeds the limit, or any error
+    /// [`Client::cached_status`] can return.
+    pub async fn check_duration(&self, duration: Duration) -> Result<(), MediaError> {
+        self.cached_status().await?.cobalt.check_duration(duration)
+    }
+
+    /// Discards the cached status, so the next [`Client::cached_status`] call refetches it.
+    pub async fn invalidate_status_cache(&self) {
+        *self.status_cache.write().await = None;
     }
 
     /// Fetches media based on the provided request data.
@@ -121,6 +631,11 @@ impl Client {
     /// - `Ok(Response)` containing the fetched media response if the request succeeds.
     /// - `Err(MediaError)` if there is a request or deserialization error.
     ///
+    /// Cobalt reports errors in two ways: usually with an HTTP error status, returned as
+    /// `Err(MediaError::Api)`, but sometimes in the body of a 200 response, returned as
+    /// `Ok(Response::Error)`. An `Ok` is therefore not necessarily a success; chain
+    /// [`Response::into_media_result`] to get both as an `Err`.
+    ///
     /// # Example
     /// ```rust
     /// use cobalt_tools::api::CobaltClient;
@@ -136,8 +651,8 @@ impl Client {
     ///     let client = CobaltClient.read().await;
     ///
     ///     let video_data = MediaRequestData {
-    ///         url: "https://www.youtube.com/watch?v=1lML-Uem6Ns".to_string(),
-    ///         filename_style: "basic".to_string(),
+    ///         url: "https://www.youtube.com/watch?v=1lML-Uem6Ns",
+    ///         filename_style: Some("basic"),
     ///         ..Default::default()
     ///     };
     ///
@@ -156,73 +671,659 @@ impl Client {
         override_api_key: Option<String>,
         video_data: MediaRequestData<'a>,
     ) -> Result<Response, MediaError> {
-        let api_key = override_api_key.unwrap_or(self.api_key.clone());
+        let options = RequestOptions {
+            api_key: override_api_key.as_deref(),
+            ..Default::default()
+        };
+
+        self.get_media_with(options, video_data).await
+    }
 
-        let serialized = serde_json::to_string(&video_data).unwrap();
+    /// Fetches media like [`Client::get_media`] with the client's own API key.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cobalt_tools::api::client::Client;
+    /// use cobalt_tools::structs::media_request::MediaRequestData;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = Client::builder("your-api-key-here", "http://localhost:9000")
+    ///         .build()
+    ///         .expect("Failed to build client");
+    ///
+    ///     let video_data = MediaRequestData {
+    ///         url: "https://www.youtube.com/watch?v=1lML-Uem6Ns",
+    ///         ..Default::default()
+    ///     };
+    ///
+    ///     match client.get_media_default(video_data).await {
+    ///         Ok(response) => println!("Response: {:#?}", response),
+    ///         Err(media_error) => eprintln!("Media Error: {}", media_error),
+    ///     }
+    /// }
+    /// ```
+    pub async fn get_media_default<'a>(
+        &self,
+        video_data: MediaRequestData<'a>,
+    ) -> Result<Response, MediaError> {
+        self.get_media_with(RequestOptions::default(), video_data)
+            .await
+    }
 
-        let client = ReqwestClient::new();
-        let response = client
-            .post(self.instance_uri.clone())
-            .header("Content-Type", "application/json")
-            .header("Accept", "application/json")
-            .header("User-Agent", "Cobalt")
-            .header("Authorization", format!("Api-Key {}", api_key))
-            .body(serialized)
-            .send()
+    /// Fetches media like [`Client::get_media`], applying per-call overrides.
+    ///
+    /// Headers not set in `options` fall back to the client's defaults, which are
+    /// `application/json` unless configured otherwise on the [`ClientBuilder`].
+    ///
+    /// # Example
+    /// ```rust
+    /// use cobalt_tools::api::{client::Client, options::RequestOptions};
+    /// use cobalt_tools::structs::media_request::MediaRequestData;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = Client::builder("your-api-key-here", "http://localhost:9000")
+    ///         .build()
+    ///         .expect("Failed to build client");
+    ///
+    ///     let options = RequestOptions {
+    ///         accept: Some("application/json, text/plain"),
+    ///         ..Default::default()
+    ///     };
+    ///
+    ///     let video_data = MediaRequestData {
+    ///         url: "https://www.youtube.com/watch?v=1lML-Uem6Ns",
+    ///         filename_style: Some("basic"),
+    ///         ..Default::default()
+    ///     };
+    ///
+    ///     if let Err(media_error) = client.get_media_with(options, video_data).await {
+    ///         eprintln!("Media Error: {:#?}", media_error);
+    ///     }
+    /// }
+    /// ```
+    pub async fn get_media_with<'a>(
+        &self,
+        options: RequestOptions<'_>,
+        video_data: MediaRequestData<'a>,
+    ) -> Result<Response, MediaError> {
+        video_data.validate()?;
+
+        self.post_media(options, &video_data).await
+    }
+
+    /// Requests `url` once for every [`VideoQuality`], a few at a time, to find out which
+    /// qualities the instance can deliver, e.g. to let a user choose before downloading.
+    ///
+    /// Results come best quality first. When several qualities resolve to the same file,
+    /// e.g. `max` and `1080` for a 1080p video, only the best quality is kept. Redirects
+    /// count as the same file when their filenames match, as tunnel URLs differ on every
+    /// request; errors are always kept.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cobalt_tools::api::client::Client;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = Client::builder("your-api-key-here", "http://localhost:9000")
+    ///         .build()
+    ///         .expect("Failed to build client");
+    ///
+    ///     let url = "https://www.youtube.com/watch?v=1lML-Uem6Ns";
+    ///     for (quality, result) in client.get_media_all_qualities(url).await {
+    ///         if result.is_ok_and(|response| response.is_single_file()) {
+    ///             println!("{} is available", quality.as_str());
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub async fn get_media_all_qualities(
+        &self,
+        url: &str,
+    ) -> Vec<(VideoQuality, Result<Response, MediaError>)> {
+        let results: Vec<_> = futures_util::stream::iter(VideoQuality::ALL)
+            .map(|&quality| async move {
+                let video_data = MediaRequestData {
+                    url,
+                    video_quality: Some(quality.as_str()),
+                    ..Default::default()
+                };
+                (quality, self.get_media_default(video_data).await)
+            })
+            .buffered(QUALITY_CONCURRENCY)
+            .collect()
             .await;
 
-        let response = match response {
-            Ok(r) => r,
-            Err(e) => {
-                return Err(MediaError::RequestError(format!(
-                    "Failed to send request: {}",
-                    e
-                )))
-            }
+        let mut seen = HashSet::new();
+        results
+            .into_iter()
+            .filter(|(_, result)| {
+                let key = match result {
+                    Ok(Response::Redirect(redirect)) => redirect.filename.clone(),
+                    Ok(Response::Picker(picker)) => {
+                        serde_json::to_string(picker).unwrap_or_default()
+                    }
+                    Ok(Response::Error(_)) | Err(_) => return true,
+                };
+                seen.insert(key)
+            })
+            .collect()
+    }
+
+    /// Fetches media like [`Client::get_media`], also reporting how long the call took
+    /// and how many bytes of response it received.
+    ///
+    /// The stats are returned even when the request fails. They include the request's
+    /// `X-Request-Id` when the client generates them, see [`ClientBuilder::request_ids`].
+    ///
+    /// # Example
+    /// ```rust
+    /// use cobalt_tools::api::client::Client;
+    /// use cobalt_tools::structs::media_request::MediaRequestData;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = Client::builder("your-api-key-here", "http://localhost:9000")
+    ///         .build()
+    ///         .expect("Failed to build client");
+    ///
+    ///     let video_data = MediaRequestData {
+    ///         url: "https://www.youtube.com/watch?v=1lML-Uem6Ns",
+    ///         ..Default::default()
+    ///     };
+    ///
+    ///     let (result, stats) = client.get_media_timed(None, video_data).await;
+    ///     println!("{:?} in {:?} ({} bytes)", result.is_ok(), stats.elapsed, stats.bytes);
+    /// }
+    /// ```
+    pub async fn get_media_timed(
+        &self,
+        override_api_key: Option<String>,
+        video_data: MediaRequestData<'_>,
+    ) -> (Result<Response, MediaError>, RequestStats) {
+        let start = self.clock.now();
+        let request_id = self.request_id(None);
+        let options = RequestOptions {
+            api_key: override_api_key.as_deref(),
+            request_id: request_id.as_deref(),
+            ..Default::default()
+        };
+
+        let mut bytes = 0;
+        let result = match video_data.validate() {
+            Ok(()) => match self.post_media_bytes(options, &video_data).await {
+                Ok((content_type, body)) => {
+                    bytes = body.len() as u64;
+                    media_response::parse_body(content_type.as_deref(), &body)
+                }
+                Err(err) => Err(err),
+            },
+            Err(err) => Err(err),
+        };
+
+        let stats = RequestStats {
+            elapsed: self.clock.now().saturating_duration_since(start),
+            bytes,
+            request_id,
+        };
+        (result, stats)
+    }
+
+    /// Fetches media like [`Client::get_media`], returning the response body unparsed.
+    ///
+    /// Use this to parse responses with a different JSON library or serde configuration;
+    /// [`Response::from_bytes`] parses them the way `get_media` does.
+    ///
+    /// # Errors
+    /// Returns the same errors as [`Client::get_media`], except for parse errors.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cobalt_tools::api::client::Client;
+    /// use cobalt_tools::structs::{media_request::MediaRequestData, media_response::Response};
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = Client::builder("your-api-key-here", "http://localhost:9000")
+    ///         .build()
+    ///         .expect("Failed to build client");
+    ///
+    ///     let video_data = MediaRequestData {
+    ///         url: "https://www.youtube.com/watch?v=1lML-Uem6Ns",
+    ///         ..Default::default()
+    ///     };
+    ///
+    ///     if let Ok(body) = client.get_media_bytes(None, video_data).await {
+    ///         println!("{:?}", Response::from_bytes(&body));
+    ///     }
+    /// }
+    /// ```
+    pub async fn get_media_bytes(
+        &self,
+        override_api_key: Option<String>,
+        video_data: MediaRequestData<'_>,
+    ) -> Result<Bytes, MediaError> {
+        let options = RequestOptions {
+            api_key: override_api_key.as_deref(),
+            ..Default::default()
+        };
+
+        video_data.validate()?;
+
+        let (content_type, body) = self.post_media_bytes(options, &video_data).await?;
+        media_response::check_content_type(content_type.as_deref())?;
+
+        Ok(body)
+    }
+
+    /// Posts an arbitrary JSON body to the instance and parses the answer as a [`Response`].
+    ///
+    /// This is an escape hatch for cobalt options the crate doesn't model yet; the body is
+    /// sent as is, without the checks [`Client::get_media`] performs.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cobalt_tools::api::client::Client;
+    /// use serde_json::json;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = Client::builder("your-api-key-here", "http://localhost:9000")
+    ///         .build()
+    ///         .expect("Failed to build client");
+    ///
+    ///     let body = json!({
+    ///         "url": "https://www.youtube.com/watch?v=1lML-Uem6Ns",
+    ///         "someNewOption": true,
+    ///     });
+    ///
+    ///     if let Err(media_error) = client.get_media_raw(None, body).await {
+    ///         eprintln!("Media Error: {:#?}", media_error);
+    ///     }
+    /// }
+    /// ```
+    pub async fn get_media_raw(
+        &self,
+        override_api_key: Option<String>,
+        body: impl Serialize,
+    ) -> Result<Response, MediaError> {
+        let options = RequestOptions {
+            api_key: override_api_key.as_deref(),
+            ..Default::default()
+        };
+
+        self.post_media(options, &body).await
+    }
+
+    /// Fetches media like [`Client::get_media`], also returning the response as raw JSON.
+    ///
+    /// The body is parsed once into a [`serde_json::Value`] and the [`Response`] is read
+    /// from it, so fields the typed structs don't model yet remain accessible.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cobalt_tools::api::client::Client;
+    /// use cobalt_tools::structs::media_request::MediaRequestData;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = Client::builder("your-api-key-here", "http://localhost:9000")
+    ///         .build()
+    ///         .expect("Failed to build client");
+    ///
+    ///     let video_data = MediaRequestData {
+    ///         url: "https://www.youtube.com/watch?v=1lML-Uem6Ns",
+    ///         ..Default::default()
+    ///     };
+    ///
+    ///     if let Ok((response, value)) = client.get_media_value(None, video_data).await {
+    ///         println!("{:?} with status {}", response.get_status(), value["status"]);
+    ///     }
+    /// }
+    /// ```
+    pub async fn get_media_value(
+        &self,
+        override_api_key: Option<String>,
+        video_data: MediaRequestData<'_>,
+    ) -> Result<(Response, Value), MediaError> {
+        let options = RequestOptions {
+            api_key: override_api_key.as_deref(),
+            ..Default::default()
         };
 
+        video_data.validate()?;
+
+        let value: Value = self.post_media(options, &video_data).await?;
+        let response = Response::deserialize(&value)?;
+
+        Ok((response, value))
+    }
+
+    async fn post_media<T: Serialize + ?Sized, R: DeserializeOwned>(
+        &self,
+        options: RequestOptions<'_>,
+        body: &T,
+    ) -> Result<R, MediaError> {
+        let (content_type, body) = self.post_media_bytes(options, body).await?;
+
+        media_response::parse_body(content_type.as_deref(), &body)
+    }
+
+    /// Posts `body` like `post_media`, returning the response's `Content-Type` and its
+    /// unparsed body.
+    async fn post_media_bytes<T: Serialize + ?Sized>(
+        &self,
+        options: RequestOptions<'_>,
+        body: &T,
+    ) -> Result<(Option<String>, Bytes), MediaError> {
+        let api_key = options.api_key.unwrap_or(&self.api_key);
+        let accept = options.accept.unwrap_or(&self.accept);
+        let content_type = options.content_type.unwrap_or(&self.content_type);
+
+        let serialized = serialize_body(body, self.pretty_json, self.bools_as_strings)?;
+        let auth_scheme = self.request_auth_scheme().await;
+
+        let mut request = self
+            .http
+            .post(self.instance_uri.clone())
+            .query(&self.query)
+            .query(options.query)
+            .header("Content-Type", content_type)
+            .header("Accept", accept)
+            .header("User-Agent", &self.user_agent)
+            .header("Authorization", auth_scheme.header_value(api_key))
+            .body(serialized);
+        if let Some(accept_language) = &self.accept_language {
+            request = request.header("Accept-Language", accept_language);
+        }
+        if let Some(request_id) = self.request_id(options.request_id) {
+            request = request.header("X-Request-Id", request_id);
+        }
+        if self.offline {
+            return self.offline_response(request);
+        }
+        let response = self.execute(request).await?;
+
         if !response.status().is_success() {
-            return Err(MediaError::ApiError(format!(
-                "API request failed with status: {} | {:?}",
-                response.status(),
-                response.text().await
+            return Err(MediaError::from_response(response).await);
+        }
+
+        let content_type = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        let body = response.bytes().await?;
+
+        Ok((content_type, body))
+    }
+
+    /// Returns `request_id`, or a generated ID if it's unset and the client generates them.
+    fn request_id(&self, request_id: Option<&str>) -> Option<String> {
+        match request_id {
+            Some(request_id) => Some(request_id.to_string()),
+            None if self.request_ids => Some(new_request_id()),
+            None => None,
+        }
+    }
+
+    /// Returns the `Authorization` scheme for a media request, negotiating it from the
+    /// cached status if [`ClientBuilder::negotiate_auth`] is enabled.
+    async fn request_auth_scheme(&self) -> AuthScheme {
+        if !self.negotiate_auth {
+            return self.auth_scheme;
+        }
+
+        match self.cached_status().await {
+            Ok(status) => status.cobalt.auth_scheme(self.auth_scheme),
+            Err(_) => self.auth_scheme,
+        }
+    }
+
+    /// Answers a media request with the stub response of [`ClientBuilder::offline`],
+    /// returning its `Content-Type` and body like `post_media_bytes`.
+    fn offline_response(
+        &self,
+        request: RequestBuilder,
+    ) -> Result<(Option<String>, Bytes), MediaError> {
+        let request = request.build()?;
+
+        if let Some(interceptor) = &self.interceptor {
+            interceptor.before_request(&request);
+        }
+
+        let body = request.body().and_then(|body| body.as_bytes());
+        Ok(offline_stub(body.unwrap_or_default()))
+    }
+
+    /// Sends `request` through the shared HTTP client, notifying the interceptor.
+    ///
+    /// With the `otel` feature, the request runs in an HTTP client span until the
+    /// response headers arrive, and carries trace context from the client's
+    /// [propagator](ClientBuilder::propagator).
+    ///
+    /// Fails without sending anything if the client is [offline](ClientBuilder::offline).
+    pub(crate) async fn execute(
+        &self,
+        request: RequestBuilder,
+    ) -> Result<reqwest::Response, MediaError> {
+        #[cfg_attr(not(feature = "otel"), allow(unused_mut))]
+        let mut request = request.build()?;
+
+        #[cfg(feature = "otel")]
+        let span = super::otel::request_span(&request);
+        #[cfg(feature = "otel")]
+        span.in_scope(|| self.propagator.inject(request.headers_mut()));
+
+        if let Some(interceptor) = &self.interceptor {
+            interceptor.before_request(&request);
+        }
+
+        if self.offline {
+            return Err(MediaError::RequestError(format!(
+                "offline client: {} {} was not sent",
+                request.method(),
+                request.url()
             )));
         }
+        if self.is_shut_down() {
+            return Err(MediaError::Cancelled);
+        }
+        tls::check_https(self.tls_error.as_ref(), request.url().as_str())?;
 
-        let final_response: Response = match response.json().await {
-            Ok(res) => res,
-            Err(e) => {
-                return Err(MediaError::DeserializationError(format!(
-                    "Failed to parse response: {}",
-                    e
-                )))
-            }
-        };
+        let response = self.http.execute(request);
+        #[cfg(feature = "otel")]
+        let response = tracing::Instrument::instrument(response, span.clone());
+        let response = self
+            .until_shutdown(response)
+            .await
+            .and_then(|response| response.map_err(MediaError::from));
+
+        #[cfg(feature = "otel")]
+        super::otel::record_response(&span, &response);
+        let response = response?;
 
-        Ok(final_response)
+        if let Some(interceptor) = &self.interceptor {
+            interceptor.after_response(&response);
+        }
+
+        Ok(response)
     }
 
-    pub async fn download(
-        tunnel_link: String,
-        path: String,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        let response = reqwest::get(&tunnel_link).await?;
+    /// Downloads `tunnel_link` to `path` with a fresh HTTP client.
+    ///
+    /// The HTTP client is unconfigured: it has reqwest's defaults for timeouts, proxies,
+    /// and the `User-Agent`, and no custom root certificates, and it isn't affected by
+    /// [`Client::shutdown`]. To download with a client's settings, use
+    /// [`Client::download_with`] on that client instead.
+    ///
+    /// # Errors
+    /// Returns `MediaError::Api` if the server answers with an error status,
+    /// `MediaError::IoError` if the file can't be written, `MediaError::ClientInit` if
+    /// the `rustls` feature finds no root certificates for an HTTPS link, or `MediaError::RequestError` if
+    /// the connection fails.
+    pub async fn download(tunnel_link: String, path: String) -> Result<DownloadReport, MediaError> {
+        let start = Instant::now();
+        tls::check_https(tls::missing_roots(false).as_ref(), &tunnel_link)?;
+        let client = tls::configure(ReqwestClient::builder()).build()?;
+        let response = client.get(&tunnel_link).send().await?;
     
         if !response.status().is_success() {
-            eprintln!("Failed to download file: HTTP {}", response.status());
-            return Err(format!("Failed to download file: HTTP {}", response.status()).into());
+            return Err(MediaError::from_response(response).await);
         }
     
-        let mut file = File::create(path).expect("Failed to create file");
+        let content_type = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        let etag = response
+            .headers()
+            .get(ETAG)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+
+        let mut file = File::create(&path)?;
+        let mut bytes = 0;
     
         let mut content = response.bytes_stream();
         while let Some(chunk) = content.next().await {
             let chunk = chunk?;
             file.write_all(&chunk)?;
+            bytes += chunk.len() as u64;
         }
     
-        Ok(())
+        Ok(DownloadReport {
+            path: path.into(),
+            bytes,
+            duration: start.elapsed(),
+            content_type,
+            resumed: false,
+            etag,
+        })
+    }
+}
+
+/// The URL [`Client::healthcheck`] requests, which no cobalt service handles.
+const HEALTHCHECK_URL: &str = "https://example.com/";
+
+/// How many requests [`Client::get_media_all_qualities`] sends at once.
+const QUALITY_CONCURRENCY: usize = 4;
+
+/// Serializes a media request body as configured with [`ClientBuilder::pretty_json`] and
+/// [`ClientBuilder::bools_as_strings`].
+pub(crate) fn serialize_body<T: Serialize + ?Sized>(
+    body: &T,
+    pretty: bool,
+    bools_as_strings: bool,
+) -> Result<String, MediaError> {
+    if !bools_as_strings && pretty {
+        return Ok(serde_json::to_string_pretty(body)?);
+    }
+    if !bools_as_strings {
+        return Ok(serde_json::to_string(body)?);
+    }
+
+    // Going through `Value` orders the fields by name, which cobalt doesn't mind.
+    let mut value = serde_json::to_value(body)?;
+    if let Value::Object(fields) = &mut value {
+        for field in fields.values_mut() {
+            if let Value::Bool(flag) = field {
+                *field = Value::String(flag.to_string());
+            }
+        }
+    }
+
+    serialize_body(&value, pretty, false)
+}
+
+/// Fails the API key check of [`Client::healthcheck`] for authentication error codes.
+fn auth_check(code: String) -> CheckOutcome {
+    if code.starts_with("error.api.auth.") {
+        CheckOutcome::Failed(code)
+    } else {
+        CheckOutcome::Passed
+    }
+}
+
+/// Returns the `Content-Type` and body of the stub response an
+/// [offline](ClientBuilder::offline) client answers the media request `body` with.
+pub(crate) fn offline_stub(body: &[u8]) -> (Option<String>, Bytes) {
+    let key = format!("{:016x}", fnv1a(body));
+    let stub = serde_json::json!({
+        "status": "redirect",
+        "url": format!("http://offline.invalid/{}", key),
+        "filename": key,
+    });
+
+    (
+        Some("application/json".to_string()),
+        Bytes::from(stub.to_string()),
+    )
+}
+
+/// Generates a random (version 4) UUID for `X-Request-Id`.
+pub(crate) fn new_request_id() -> String {
+    let random = |salt: u8| {
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u8(salt);
+        hasher.finish()
+    };
+    let high = (random(0) & !0xF000) | 0x4000;
+    let low = (random(1) & !(0b11 << 62)) | (0b10 << 62);
+
+    format!(
+        "{:08x}-{:04x}-{:04x}-{:04x}-{:012x}",
+        high >> 32,
+        (high >> 16) & 0xFFFF,
+        high & 0xFFFF,
+        low >> 48,
+        low & 0xFFFF_FFFF_FFFF
+    )
+}
+
+/// Masks `secret` for display, keeping its first and last 4 characters only if at
+/// least 4 more stay hidden.
+pub(crate) fn mask_secret(secret: &str) -> String {
+    const SHOWN: usize = 4;
+    const MASK: &str = "****";
+
+    let chars: Vec<char> = secret.chars().collect();
+    if chars.len() < SHOWN * 3 {
+        return MASK.to_string();
     }
+
+    let head: String = chars[..SHOWN].iter().collect();
+    let tail: String = chars[chars.len() - SHOWN..].iter().collect();
+    format!("{}{}{}", head, MASK, tail)
+}
+
+/// Reads and checks `API_KEY` and `INSTANCE_URI`, returning them in that order.
+fn env_config() -> Result<(String, String), ClientInitError> {
+    let api_key = env::var("API_KEY").map_err(|_| ClientInitError::MissingVar("API_KEY"))?;
+    let instance_uri =
+        env::var("INSTANCE_URI").map_err(|_| ClientInitError::MissingVar("INSTANCE_URI"))?;
+
+    check_instance_uri(&instance_uri)?;
+
+    Ok((api_key, instance_uri))
+}
+
+/// Checks that `instance_uri` is an absolute `http` or `https` URL.
+pub(crate) fn check_instance_uri(instance_uri: &str) -> Result<(), ClientInitError> {
+    let invalid = |reason: String| ClientInitError::InvalidUri {
+        uri: instance_uri.to_string(),
+        reason,
+    };
+    let url = reqwest::Url::parse(instance_uri).map_err(|error| invalid(error.to_string()))?;
+
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(invalid(format!("unsupported scheme {:?}", url.scheme())));
+    }
+
+    Ok(())
 }
 
 unsafe impl Send for Client {}
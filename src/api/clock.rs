@@ -0,0 +1,99 @@
+use futures_util::future::{self, BoxFuture};
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// The source of time for the client's status cache, retry delays and deadlines, and
+/// timing stats such as download durations and speeds.
+///
+/// Set one with [`ClientBuilder::clock`](super::builder::ClientBuilder::clock); the
+/// default is [`SystemClock`]. A [`MockClock`] lets tests expire the cache or run
+/// through retries without waiting. Timeouts enforced while waiting on the network,
+/// such as [`DownloadOptions`](super::options::DownloadOptions) deadlines and idle
+/// timeouts, still use tokio's timer and the real time.
+pub trait Clock: Send + Sync {
+    /// Returns the current time.
+    fn now(&self) -> Instant;
+
+    /// Waits for `duration`, e.g. between two retries.
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()>;
+}
+
+/// Reads the system's monotonic clock and sleeps on tokio's timer.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+        Box::pin(tokio::time::sleep(duration))
+    }
+}
+
+/// A clock that only moves when told to, for deterministic tests.
+///
+/// It starts at the time it was created. Sleeping advances it by the slept duration and
+/// returns right away. Clones share the same time, so keep one to advance the clock of a
+/// client built with another.
+///
+/// # Example
+/// ```rust
+/// use cobalt_tools::api::{client::Client, clock::MockClock};
+/// use std::{sync::Arc, time::Duration};
+///
+/// let clock = MockClock::new();
+/// let client = Client::builder("your-api-key-here", "http://localhost:9000")
+///     .clock(Arc::new(clock.clone()))
+///     .build()
+///     .expect("Failed to build client");
+///
+/// // Let the cached status expire.
+/// clock.advance(Duration::from_secs(61));
+/// assert_eq!(clock.elapsed(), Duration::from_secs(61));
+/// ```
+#[derive(Debug, Clone)]
+pub struct MockClock {
+    start: Instant,
+    offset: Arc<Mutex<Duration>>,
+}
+
+impl MockClock {
+    /// Creates a clock standing at the current time that only moves when advanced.
+    pub fn new() -> Self {
+        MockClock {
+            start: Instant::now(),
+            offset: Arc::new(Mutex::new(Duration::ZERO)),
+        }
+    }
+
+    /// Moves the clock forward by `duration`.
+    pub fn advance(&self, duration: Duration) {
+        *self.offset.lock().unwrap() += duration;
+    }
+
+    /// Returns how far the clock has been moved since it was created.
+    pub fn elapsed(&self) -> Duration {
+        *self.offset.lock().unwrap()
+    }
+}
+
+impl Default for MockClock {
+    fn default() -> Self {
+        MockClock::new()
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> Instant {
+        self.start + self.elapsed()
+    }
+
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+        self.advance(duration);
+        Box::pin(future::ready(()))
+    }
+}
@@ -0,0 +1,62 @@
+use serde::Deserialize;
+use std::time::Duration;
+
+use super::builder::ClientBuilder;
+
+/// Client settings that can be deserialized from an application's own configuration.
+///
+/// The crate performs no file or environment IO here; load the struct from TOML, JSON or
+/// any other serde format and hand it to [`Client::from_config`](super::client::Client::from_config).
+/// Optional fields left out fall back to the [`ClientBuilder`] defaults.
+///
+/// # Example
+/// ```rust
+/// use cobalt_tools::api::{client::Client, config::CobaltConfig};
+///
+/// let config: CobaltConfig = serde_json::from_str(
+///     r#"{ "api_key": "your-api-key-here", "instance_uri": "http://localhost:9000", "timeout_secs": 30 }"#,
+/// )
+/// .expect("Invalid config");
+///
+/// let client = Client::from_config(config).expect("Failed to build client");
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct CobaltConfig {
+    pub api_key: String,
+    pub instance_uri: String,
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+    #[serde(default)]
+    pub user_agent: Option<String>,
+    #[serde(default)]
+    pub accept: Option<String>,
+    #[serde(default)]
+    pub content_type: Option<String>,
+    #[serde(default)]
+    pub accept_language: Option<String>,
+}
+
+impl CobaltConfig {
+    /// Converts the config into a [`ClientBuilder`] for further customization.
+    pub fn into_builder(self) -> ClientBuilder {
+        let mut builder = ClientBuilder::new(self.api_key, self.instance_uri);
+
+        if let Some(timeout_secs) = self.timeout_secs {
+            builder = builder.timeout(Duration::from_secs(timeout_secs));
+        }
+        if let Some(user_agent) = self.user_agent {
+            builder = builder.user_agent(user_agent);
+        }
+        if let Some(accept) = self.accept {
+            builder = builder.accept(accept);
+        }
+        if let Some(content_type) = self.content_type {
+            builder = builder.content_type(content_type);
+        }
+        if let Some(accept_language) = self.accept_language {
+            builder = builder.accept_language(accept_language);
+        }
+
+        builder
+    }
+}
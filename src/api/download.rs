@@ -0,0 +1,1383 @@
+use bytes::Bytes;
+use futures_util::{Stream, StreamExt};
+use reqwest::{
+    header::{CONTENT_RANGE, CONTENT_TYPE, ETAG, IF_NONE_MATCH, IF_RANGE, RANGE},
+    RequestBuilder, Response, StatusCode,
+};
+use std::{
+    collections::HashSet,
+    fs::{self, File, OpenOptions},
+    future::Future,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+use tokio::sync::{
+    mpsc::{self, Sender},
+    Notify,
+};
+
+use crate::structs::{
+    archive::ZipWriter,
+    download::{
+        eta, BatchEvent, BatchProgress, DownloadEvent, DownloadJob, DownloadOutcome,
+        DownloadReport, DownloadStatus, Progress,
+    },
+    filename::sanitize_filename,
+    media_error::MediaError,
+    media_request::{fnv1a, MediaRequestData},
+    media_response::{MediaKind, PickerResponse, Response as MediaResponse},
+    service::page_service_for_url,
+    stats::RequestStats,
+};
+
+use super::{
+    client::Client,
+    clock::Clock,
+    options::{BatchMode, CollisionPolicy, DownloadOptions},
+};
+
+impl Client {
+    /// Downloads a tunnel or redirect URL to `path` using the client's HTTP connection pool.
+    ///
+    /// When `options.if_none_match` is set, the request carries an `If-None-Match` header
+    /// and a `304 Not Modified` answer returns [`DownloadStatus::NotModified`] without
+    /// touching `path`. Servers that ignore the header simply send the file again.
+    ///
+    /// A response with `Content-Encoding: gzip`, `br` or `deflate` is decompressed while
+    /// it streams, so `path` holds the media itself. The sizes in the report and in
+    /// [`Progress`] count decompressed bytes, and the total is unknown for such
+    /// responses, as `Content-Length` only gives the compressed size.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cobalt_tools::api::{client::Client, options::DownloadOptions};
+    /// use cobalt_tools::structs::download::DownloadStatus;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = Client::builder("your-api-key-here", "http://localhost:9000")
+    ///         .build()
+    ///         .expect("Failed to build client");
+    ///
+    ///     let options = DownloadOptions {
+    ///         if_none_match: Some("\"5d8c72a5edda8d6a\""),
+    ///         ..Default::default()
+    ///     };
+    ///
+    ///     match client.download_with("http://localhost:9000/tunnel", "video.mp4", options).await {
+    ///         Ok(DownloadStatus::Downloaded(report)) => println!("Downloaded, new ETag: {:?}", report.etag),
+    ///         Ok(DownloadStatus::NotModified) => println!("Unchanged"),
+    ///         Err(err) => eprintln!("Download failed: {}", err),
+    ///     }
+    /// }
+    /// ```
+    pub async fn download_with(
+        &self,
+        tunnel_link: &str,
+        path: impl AsRef<Path>,
+        options: DownloadOptions<'_>,
+    ) -> Result<DownloadStatus, MediaError> {
+        self.download_with_progress(tunnel_link, path, options, |_| {})
+            .await
+    }
+
+    /// Requests media and, if the instance answers with a tunnel or redirect, downloads
+    /// it into `dir` under the file name the instance suggests.
+    ///
+    /// Pickers and cobalt errors are returned as they are rather than as errors, so the
+    /// caller can choose a picker item or report the error code.
+    ///
+    /// If the instance links to another media page of a supported service instead of a
+    /// file, that page is resolved in turn, up to
+    /// [`ClientBuilder::max_resolution_depth`](super::builder::ClientBuilder::max_resolution_depth)
+    /// times. Links to other subdomains of a service, such as `v.redd.it`, are taken to
+    /// be files; see [`page_service_for_url`].
+    ///
+    /// If a file with the media's name already exists in `dir`, the name is chosen by
+    /// [`ClientBuilder::collision_policy`](super::builder::ClientBuilder::collision_policy).
+    ///
+    /// # Errors
+    /// Returns `MediaError::TooManyRedirects` if the depth is exceeded,
+    /// `MediaError::FileExists` if the file exists and the collision policy is
+    /// [`CollisionPolicy::Error`], or any error [`Client::get_media`] or
+    /// [`Client::download_with`] can return.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cobalt_tools::api::client::Client;
+    /// use cobalt_tools::structs::{download::DownloadOutcome, media_request::MediaRequestData};
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = Client::builder("your-api-key-here", "http://localhost:9000")
+    ///         .build()
+    ///         .expect("Failed to build client");
+    ///
+    ///     let video_data = MediaRequestData {
+    ///         url: "https://www.youtube.com/watch?v=1lML-Uem6Ns",
+    ///         ..Default::default()
+    ///     };
+    ///
+    ///     match client.download_media(None, video_data, "downloads").await {
+    ///         Ok(DownloadOutcome::Downloaded(report)) => println!("Saved {}", report.path.display()),
+    ///         Ok(DownloadOutcome::Picker(picker)) => println!("{} items to choose from", picker.picker.len()),
+    ///         Ok(DownloadOutcome::Failed(error)) => eprintln!("{}", error),
+    ///         Err(err) => eprintln!("Download failed: {}", err),
+    ///     }
+    /// }
+    /// ```
+    pub async fn download_media(
+        &self,
+        override_api_key: Option<String>,
+        video_data: MediaRequestData<'_>,
+        dir: impl AsRef<Path>,
+    ) -> Result<DownloadOutcome, MediaError> {
+        let mut depth = 0;
+        let mut resolved_url = None;
+        let redirect = loop {
+            let request = MediaRequestData {
+                url: resolved_url.as_deref().unwrap_or(video_data.url),
+                ..video_data.clone()
+            };
+            let redirect = match self.get_media(override_api_key.clone(), request).await? {
+                MediaResponse::Redirect(redirect) => redirect,
+                MediaResponse::Picker(picker) => return Ok(DownloadOutcome::Picker(picker)),
+                MediaResponse::Error(error) => return Ok(DownloadOutcome::Failed(error)),
+            };
+
+            if page_service_for_url(&redirect.url).is_none() {
+                break redirect;
+            }
+            if depth == self.max_resolution_depth {
+                return Err(MediaError::TooManyRedirects {
+                    limit: self.max_resolution_depth,
+                });
+            }
+            depth += 1;
+            resolved_url = Some(redirect.url);
+        };
+
+        let filename = sanitize_filename(&redirect.filename);
+        let filename = Path::new(&filename);
+        let stem = filename
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or("media");
+        let ext = filename.extension().and_then(|ext| ext.to_str());
+        let dir = dir.as_ref();
+        let stem = self.free_stem(dir, stem, &[ext], &redirect.url, &HashSet::new())?;
+        let path = dir.join(with_extension(&stem, ext));
+        match self
+            .download_with(&redirect.url, path, DownloadOptions::default())
+            .await?
+        {
+            DownloadStatus::Downloaded(report) => Ok(DownloadOutcome::Downloaded(report)),
+            // No `If-None-Match` was sent, so a 304 answer is a server error.
+            DownloadStatus::NotModified => Err(MediaError::Api {
+                status: StatusCode::NOT_MODIFIED,
+                body: String::new(),
+            }),
+        }
+    }
+
+    /// Downloads like [`Client::download_with`], reporting [`Progress`] after every chunk.
+    ///
+    /// The reported speed is an exponential moving average over recent chunks, so it
+    /// stays stable enough to display directly.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cobalt_tools::api::{client::Client, options::DownloadOptions};
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = Client::builder("your-api-key-here", "http://localhost:9000")
+    ///         .build()
+    ///         .expect("Failed to build client");
+    ///
+    ///     let result = client
+    ///         .download_with_progress(
+    ///             "http://localhost:9000/tunnel",
+    ///             "video.mp4",
+    ///             DownloadOptions::default(),
+    ///             |progress| println!("{} bytes at {:.1} MB/s", progress.downloaded, progress.speed / 1e6),
+    ///         )
+    ///         .await;
+    ///
+    ///     if let Err(err) = result {
+    ///         eprintln!("Download failed: {}", err);
+    ///     }
+    /// }
+    /// ```
+    pub async fn download_with_progress(
+        &self,
+        tunnel_link: &str,
+        path: impl AsRef<Path>,
+        options: DownloadOptions<'_>,
+        on_progress: impl FnMut(Progress),
+    ) -> Result<DownloadStatus, MediaError> {
+        let path = path.as_ref();
+        if !options.atomic {
+            return self
+                .download_file(tunnel_link, path, options, on_progress)
+                .await;
+        }
+
+        let part = PartFile::new(path, !options.resume);
+        let status = self
+            .download_file(tunnel_link, part.path(), options, on_progress)
+            .await?;
+
+        match status {
+            DownloadStatus::Downloaded(report) => {
+                part.commit(path)?;
+                Ok(DownloadStatus::Downloaded(DownloadReport {
+                    path: path.to_path_buf(),
+                    ..report
+                }))
+            }
+            DownloadStatus::NotModified => Ok(status),
+        }
+    }
+
+    /// Downloads straight to `path`; the body of [`Client::download_with_progress`].
+    async fn download_file(
+        &self,
+        tunnel_link: &str,
+        path: &Path,
+        options: DownloadOptions<'_>,
+        mut on_progress: impl FnMut(Progress),
+    ) -> Result<DownloadStatus, MediaError> {
+        // Deadlines are enforced by tokio's timer, so they count from the real time;
+        // the reported duration and speed come from the client's clock.
+        let start = Instant::now();
+        let started = self.clock.now();
+        let existing = if options.resume {
+            fs::metadata(path)
+                .map(|metadata| metadata.len())
+                .unwrap_or(0)
+        } else {
+            0
+        };
+
+        let mut deadline = earliest(
+            options.deadline,
+            options
+                .timeout
+                .and_then(|policy| policy.limit(None))
+                .and_then(|limit| start.checked_add(limit)),
+        );
+
+        let mut response = before_deadline(
+            deadline,
+            self.execute(self.download_request(tunnel_link, &options, existing)),
+        )
+        .await??;
+
+        if response.status() == StatusCode::NOT_MODIFIED {
+            return Ok(DownloadStatus::NotModified);
+        }
+
+        let mut offset = 0;
+        if existing > 0 {
+            match response.status() {
+                StatusCode::PARTIAL_CONTENT if resume_matches(&response, existing, &options) => {
+                    offset = existing;
+                }
+                StatusCode::RANGE_NOT_SATISFIABLE
+                    if content_range(&response) == Some((None, Some(existing)))
+                        && options.expected_size.unwrap_or(existing) == existing =>
+                {
+                    return Ok(DownloadStatus::Downloaded(DownloadReport {
+                        path: path.to_path_buf(),
+                        bytes: existing,
+                        duration: self.clock.now().saturating_duration_since(started),
+                        content_type: header_content_type(&response),
+                        resumed: true,
+                        etag: header_etag(&response),
+                    }));
+                }
+                StatusCode::PARTIAL_CONTENT | StatusCode::RANGE_NOT_SATISFIABLE => {
+                    response = before_deadline(
+                        deadline,
+                        self.execute(self.download_request(tunnel_link, &options, 0)),
+                    )
+                    .await??;
+                }
+                _ => {}
+            }
+        }
+
+        if !response.status().is_success() {
+            return Err(MediaError::from_response(response).await);
+        }
+
+        let etag = header_etag(&response);
+        let content_type = header_content_type(&response);
+
+        let length = response.content_length();
+        if length == Some(0) && !options.allow_empty {
+            return Err(MediaError::EmptyResponse);
+        }
+        let total = match offset {
+            0 => length,
+            _ => content_range(&response)
+                .and_then(|(_, size)| size)
+                .or(length.map(|length| offset + length)),
+        };
+
+        deadline = earliest(
+            deadline,
+            options
+                .timeout
+                .and_then(|policy| policy.limit(length))
+                .and_then(|limit| start.checked_add(limit)),
+        );
+
+        let mut file = match offset {
+            0 => File::create(path)?,
+            _ => OpenOptions::new().append(true).open(path)?,
+        };
+        let mut meter = SpeedMeter::new(&*self.clock);
+        let mut downloaded = 0;
+
+        let mut content = self.body_stream(response);
+        let idle_timeout = options.idle_timeout;
+        while let Some(chunk) =
+            before_deadline(deadline, before_idle(idle_timeout, content.next())).await??
+        {
+            let chunk = chunk?;
+            file.write_all(&chunk)?;
+
+            downloaded += chunk.len() as u64;
+            let speed = meter.record(chunk.len() as u64);
+            on_progress(Progress {
+                downloaded: offset + downloaded,
+                total,
+                speed,
+                resumed_from: offset,
+                eta: eta(offset + downloaded, total, speed),
+            });
+        }
+
+        Ok(DownloadStatus::Downloaded(DownloadReport {
+            path: path.to_path_buf(),
+            bytes: offset + downloaded,
+            duration: self.clock.now().saturating_duration_since(started),
+            content_type,
+            resumed: offset > 0,
+            etag,
+        }))
+    }
+
+    /// Downloads like [`Client::download_with`], also reporting how long the download
+    /// took and how many bytes it wrote.
+    ///
+    /// When resuming, only the bytes received by this call are counted. The stats are
+    /// returned even when the download fails.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cobalt_tools::api::{client::Client, options::DownloadOptions};
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = Client::builder("your-api-key-here", "http://localhost:9000")
+    ///         .build()
+    ///         .expect("Failed to build client");
+    ///
+    ///     let (result, stats) = client
+    ///         .download_timed("http://localhost:9000/tunnel", "video.mp4", DownloadOptions::default())
+    ///         .await;
+    ///
+    ///     match result {
+    ///         Ok(_) => println!("{} bytes in {:?}", stats.bytes, stats.elapsed),
+    ///         Err(err) => eprintln!("Download failed after {:?}: {}", stats.elapsed, err),
+    ///     }
+    /// }
+    /// ```
+    pub async fn download_timed(
+        &self,
+        tunnel_link: &str,
+        path: impl AsRef<Path>,
+        options: DownloadOptions<'_>,
+    ) -> (Result<DownloadStatus, MediaError>, RequestStats) {
+        let start = self.clock.now();
+        let mut bytes = 0;
+
+        let result = self
+            .download_with_progress(tunnel_link, path, options, |progress| {
+                bytes = progress.downloaded - progress.resumed_from;
+            })
+            .await;
+
+        let stats = RequestStats {
+            elapsed: self.clock.now().saturating_duration_since(start),
+            bytes,
+            request_id: None,
+        };
+        (result, stats)
+    }
+
+    /// Downloads only bytes `start` to `end`, inclusive, of a tunnel or redirect URL
+    /// into `writer`, e.g. to read a container header without fetching the whole file.
+    ///
+    /// Returns the number of bytes written, which is less than requested when the file
+    /// ends before `end`.
+    ///
+    /// # Errors
+    /// Returns `MediaError::RangeNotSupported` if the server answers with the whole file
+    /// instead of the requested range, and `MediaError::InvalidRequest` if `start` is
+    /// after `end`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cobalt_tools::api::client::Client;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = Client::builder("your-api-key-here", "http://localhost:9000")
+    ///         .build()
+    ///         .expect("Failed to build client");
+    ///
+    ///     let mut header = Vec::new();
+    ///     match client.download_range("http://localhost:9000/tunnel", 0, 1023, &mut header).await {
+    ///         Ok(read) => println!("Read the first {} bytes", read),
+    ///         Err(err) => eprintln!("Download failed: {}", err),
+    ///     }
+    /// }
+    /// ```
+    pub async fn download_range<W: Write>(
+        &self,
+        tunnel_link: &str,
+        start: u64,
+        end: u64,
+        writer: &mut W,
+    ) -> Result<u64, MediaError> {
+        if start > end {
+            return Err(MediaError::InvalidRequest(format!(
+                "range start {} is after its end {}",
+                start, end
+            )));
+        }
+
+        let request = self
+            .http
+            .get(tunnel_link)
+            .header(RANGE, format!("bytes={}-{}", start, end));
+        let response = self.execute(request).await?;
+
+        match response.status() {
+            StatusCode::PARTIAL_CONTENT => {}
+            StatusCode::OK => return Err(MediaError::RangeNotSupported),
+            _ => return Err(MediaError::from_response(response).await),
+        }
+
+        if !matches!(content_range(&response), Some((Some(first), _)) if first == start) {
+            return Err(MediaError::RangeNotSupported);
+        }
+
+        let mut remaining = end - start + 1;
+        let mut content = self.body_stream(response);
+        while let Some(chunk) = content.next().await {
+            let chunk = chunk?;
+            let take = chunk
+                .len()
+                .min(usize::try_from(remaining).unwrap_or(usize::MAX));
+            writer.write_all(&chunk[..take])?;
+
+            remaining -= take as u64;
+            if remaining == 0 {
+                break;
+            }
+        }
+
+        Ok(end - start + 1 - remaining)
+    }
+
+    /// Builds a download request, asking for the bytes from `offset` on when it's non-zero.
+    fn download_request(
+        &self,
+        tunnel_link: &str,
+        options: &DownloadOptions<'_>,
+        offset: u64,
+    ) -> RequestBuilder {
+        let mut request = self.http.get(tunnel_link);
+
+        if let Some(etag) = options.if_none_match {
+            request = request.header(IF_NONE_MATCH, etag);
+        }
+
+        if offset > 0 {
+            request = request.header(RANGE, format!("bytes={}-", offset));
+
+            if let Some(etag) = options.resume_etag {
+                request = request.header(IF_RANGE, etag);
+            }
+        }
+
+        request
+    }
+
+    /// Downloads like [`Client::download_with`], sending progress to a channel.
+    ///
+    /// Progress events are sent with `try_send`, so a full channel drops intermediate
+    /// updates rather than slowing the download down. The final [`DownloadEvent::Finished`]
+    /// or [`DownloadEvent::Failed`] event is always delivered while the receiver is alive.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cobalt_tools::api::{client::Client, options::DownloadOptions};
+    /// use cobalt_tools::structs::download::DownloadEvent;
+    /// use tokio::sync::mpsc;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = Client::builder("your-api-key-here", "http://localhost:9000")
+    ///         .build()
+    ///         .expect("Failed to build client");
+    ///
+    ///     let (tx, mut rx) = mpsc::channel(16);
+    ///     tokio::spawn(async move {
+    ///         let _ = client
+    ///             .download_with_channel("http://localhost:9000/tunnel", "video.mp4", DownloadOptions::default(), tx)
+    ///             .await;
+    ///     });
+    ///
+    ///     while let Some(event) = rx.recv().await {
+    ///         match event {
+    ///             DownloadEvent::Progress(progress) => println!("{} bytes", progress.downloaded),
+    ///             DownloadEvent::Finished(status) => println!("Done: {:?}", status),
+    ///             DownloadEvent::Failed(error) => eprintln!("Download failed: {}", error),
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub async fn download_with_channel(
+        &self,
+        tunnel_link: &str,
+        path: impl AsRef<Path>,
+        options: DownloadOptions<'_>,
+        tx: Sender<DownloadEvent>,
+    ) -> Result<DownloadStatus, MediaError> {
+        let result = self
+            .download_with_progress(tunnel_link, path, options, |progress| {
+                let _ = tx.try_send(DownloadEvent::Progress(progress));
+            })
+            .await;
+
+        let event = match &result {
+            Ok(status) => DownloadEvent::Finished(status.clone()),
+            Err(error) => DownloadEvent::Failed(error.to_string()),
+        };
+        let _ = tx.send(event).await;
+
+        result
+    }
+
+    /// Downloads every job in `jobs`, at most `concurrency` at a time, and streams
+    /// [`BatchEvent`]s for each of them along with the progress of the whole batch.
+    ///
+    /// The downloads run on a task spawned with [`Client::spawn`]. Like
+    /// [`Client::download_with_channel`], intermediate progress events may be dropped
+    /// when the stream isn't polled fast enough, but every job's `Started` and final
+    /// event is delivered. Jobs that haven't started yet are skipped once the stream
+    /// is dropped.
+    ///
+    /// With [`BatchMode::FailFast`], the first `Failed` event is the last one: jobs still
+    /// downloading are cancelled, leaving their partial files behind, and the remaining
+    /// jobs never start.
+    ///
+    /// # Panics
+    /// Panics if no runtime handle was set and this is called outside of a tokio runtime.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cobalt_tools::api::{client::Client, options::BatchMode};
+    /// use cobalt_tools::structs::download::{BatchEvent, DownloadJob};
+    /// use futures_util::StreamExt;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = Client::builder("your-api-key-here", "http://localhost:9000")
+    ///         .build()
+    ///         .expect("Failed to build client");
+    ///
+    ///     let jobs = vec![
+    ///         DownloadJob::new("http://localhost:9000/tunnel?id=1", "first.mp4"),
+    ///         DownloadJob::new("http://localhost:9000/tunnel?id=2", "second.mp4"),
+    ///     ];
+    ///
+    ///     let mut events = std::pin::pin!(client.download_batch(jobs, 4, BatchMode::CollectAll));
+    ///     while let Some(event) = events.next().await {
+    ///         match event {
+    ///             BatchEvent::Progress { overall, .. } => println!(
+    ///                 "{}/{} files, {} / {} bytes",
+    ///                 overall.finished, overall.jobs, overall.downloaded, overall.total
+    ///             ),
+    ///             BatchEvent::Failed { job, error, .. } => eprintln!("Job {} failed: {}", job, error),
+    ///             _ => {}
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn download_batch(
+        &self,
+        jobs: Vec<DownloadJob>,
+        concurrency: usize,
+        mode: BatchMode,
+    ) -> impl Stream<Item = BatchEvent> {
+        let (tx, rx) = mpsc::channel(64);
+        let overall = BatchProgress {
+            jobs: jobs.len(),
+            ..Default::default()
+        };
+        let sizes = vec![(0, None); jobs.len()];
+
+        let client = self.clone();
+        self.spawn(async move {
+            let abort = Notify::new();
+            let batch = futures_util::stream::iter(jobs.into_iter().enumerate())
+                .for_each_concurrent(concurrency.max(1), |(index, job)| {
+                    let client = &client;
+                    let abort = &abort;
+                    let tx = tx.clone();
+                    async move {
+                        if tx.send((index, JobUpdate::Started)).await.is_err() {
+                            return;
+                        }
+
+                        let mut last = None;
+                        let result = client
+                            .download_with_progress(
+                                &job.url,
+                                &job.path,
+                                DownloadOptions::default(),
+                                |progress| {
+                                    last = Some(progress);
+                                    let _ = tx.try_send((index, JobUpdate::Progress(progress)));
+                                },
+                            )
+                            .await
+                            .map_err(|error| error.to_string());
+
+                        let failed = result.is_err();
+                        let _ = tx.send((index, JobUpdate::Done(result, last))).await;
+
+                        if failed && mode == BatchMode::FailFast {
+                            abort.notify_one();
+                        }
+                    }
+                });
+
+            tokio::select! {
+                _ = batch => {}
+                _ = abort.notified() => {}
+            }
+        });
+
+        futures_util::stream::unfold(
+            (Some(rx), overall, sizes),
+            move |(rx, mut overall, mut sizes)| async move {
+                let mut rx = rx?;
+                let (job, update) = rx.recv().await?;
+                let event = match update {
+                    JobUpdate::Started => BatchEvent::Started { job },
+                    JobUpdate::Progress(progress) => {
+                        record_progress(&mut overall, &mut sizes[job], progress);
+                        BatchEvent::Progress {
+                            job,
+                            progress,
+                            overall,
+                        }
+                    }
+                    JobUpdate::Done(result, last) => {
+                        if let Some(progress) = last {
+                            record_progress(&mut overall, &mut sizes[job], progress);
+                        }
+                        overall.finished += 1;
+                        match result {
+                            Ok(status) => BatchEvent::Finished {
+                                job,
+                                status,
+                                overall,
+                            },
+                            Err(error) => BatchEvent::Failed {
+                                job,
+                                error,
+                                overall,
+                            },
+                        }
+                    }
+                };
+
+                // Dropping the receiver ends the stream and stops jobs still reporting.
+                let rx = match (&event, mode) {
+                    (BatchEvent::Failed { .. }, BatchMode::FailFast) => None,
+                    _ => Some(rx),
+                };
+                Some((event, (rx, overall, sizes)))
+            },
+        )
+    }
+
+    /// Streams the body of a tunnel or redirect URL without writing it anywhere.
+    ///
+    /// Nothing is read ahead: the body is only read from the connection as the stream is
+    /// polled, so a slow consumer slows the download down instead of buffering it. Use
+    /// [`Client::download_stream_buffered`] to keep reading while the consumer is busy.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cobalt_tools::api::client::Client;
+    /// use futures_util::StreamExt;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = Client::builder("your-api-key-here", "http://localhost:9000")
+    ///         .build()
+    ///         .expect("Failed to build client");
+    ///
+    ///     match client.download_stream("http://localhost:9000/tunnel").await {
+    ///         Ok(stream) => {
+    ///             let mut stream = std::pin::pin!(stream);
+    ///             while let Some(chunk) = stream.next().await {
+    ///                 match chunk {
+    ///                     Ok(bytes) => println!("Received {} bytes", bytes.len()),
+    ///                     Err(err) => eprintln!("Download failed: {}", err),
+    ///                 }
+    ///             }
+    ///         }
+    ///         Err(err) => eprintln!("Download failed: {}", err),
+    ///     }
+    /// }
+    /// ```
+    pub async fn download_stream(
+        &self,
+        tunnel_link: &str,
+    ) -> Result<impl Stream<Item = Result<Bytes, MediaError>>, MediaError> {
+        let response = self.execute(self.http.get(tunnel_link)).await?;
+
+        if !response.status().is_success() {
+            return Err(MediaError::from_response(response).await);
+        }
+
+        Ok(self.body_stream(response))
+    }
+
+    /// Streams like [`Client::download_stream`], but reads up to `buffer` chunks ahead
+    /// of the consumer in a task spawned with [`Client::spawn`].
+    ///
+    /// This smooths out a consumer that is briefly slow, e.g. an upload to another
+    /// server, while keeping memory bounded: once `buffer` chunks are waiting, reading
+    /// pauses until the consumer catches up. A `buffer` of 0 is treated as 1. Dropping
+    /// the stream stops the download.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cobalt_tools::api::client::Client;
+    /// use futures_util::StreamExt;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = Client::builder("your-api-key-here", "http://localhost:9000")
+    ///         .build()
+    ///         .expect("Failed to build client");
+    ///
+    ///     match client.download_stream_buffered("http://localhost:9000/tunnel", 16).await {
+    ///         Ok(stream) => {
+    ///             let mut stream = std::pin::pin!(stream);
+    ///             while let Some(chunk) = stream.next().await {
+    ///                 match chunk {
+    ///                     Ok(bytes) => println!("Received {} bytes", bytes.len()),
+    ///                     Err(err) => eprintln!("Download failed: {}", err),
+    ///                 }
+    ///             }
+    ///         }
+    ///         Err(err) => eprintln!("Download failed: {}", err),
+    ///     }
+    /// }
+    /// ```
+    pub async fn download_stream_buffered(
+        &self,
+        tunnel_link: &str,
+        buffer: usize,
+    ) -> Result<impl Stream<Item = Result<Bytes, MediaError>>, MediaError> {
+        let response = self.execute(self.http.get(tunnel_link)).await?;
+
+        if !response.status().is_success() {
+            return Err(MediaError::from_response(response).await);
+        }
+
+        let (tx, rx) = mpsc::channel(buffer.max(1));
+        let mut body = self.body_stream(response);
+        self.spawn(async move {
+            while let Some(chunk) = body.next().await {
+                let failed = chunk.is_err();
+                if tx.send(chunk).await.is_err() || failed {
+                    break;
+                }
+            }
+        });
+
+        Ok(futures_util::stream::unfold(rx, |mut rx| async move {
+            rx.recv().await.map(|chunk| (chunk, rx))
+        }))
+    }
+
+    /// Streams every `(tunnel_link, entry_name)` pair in `items` into a zip archive.
+    ///
+    /// Each download is written straight into its archive entry as it arrives, so no
+    /// temporary files are created. Entries are stored uncompressed. Returns
+    /// `archive_writer` once the archive is complete.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cobalt_tools::api::client::Client;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = Client::builder("your-api-key-here", "http://localhost:9000")
+    ///         .build()
+    ///         .expect("Failed to build client");
+    ///
+    ///     let items = [
+    ///         ("http://localhost:9000/tunnel?id=1", "first.mp4"),
+    ///         ("http://localhost:9000/tunnel?id=2", "second.mp4"),
+    ///     ];
+    ///
+    ///     match client.download_into_archive(&items, Vec::new()).await {
+    ///         Ok(archive) => println!("Built a {} byte archive", archive.len()),
+    ///         Err(err) => eprintln!("Archive failed: {}", err),
+    ///     }
+    /// }
+    /// ```
+    pub async fn download_into_archive<W: Write>(
+        &self,
+        items: &[(&str, &str)],
+        archive_writer: W,
+    ) -> Result<W, MediaError> {
+        let mut zip = ZipWriter::new(archive_writer);
+
+        for (tunnel_link, entry_name) in items {
+            let stream = self.download_stream(tunnel_link).await?;
+            let mut stream = std::pin::pin!(stream);
+
+            zip.start_entry(entry_name)?;
+            while let Some(chunk) = stream.next().await {
+                zip.write_data(&chunk?)?;
+            }
+        }
+
+        Ok(zip.finish()?)
+    }
+
+    /// Determines what kind of media a tunnel or redirect URL serves.
+    ///
+    /// Redirect responses don't say whether they point to a video, audio or an image,
+    /// so this sends a `HEAD` request and classifies the returned `Content-Type`. For
+    /// picker items, [`MediaItem::media_kind`](crate::structs::media_response::MediaItem::media_kind)
+    /// answers the same question without a request.
+    ///
+    /// Returns `Ok(None)` if the server sends no recognizable media type.
+    pub async fn probe_media_kind(&self, url: &str) -> Result<Option<MediaKind>, MediaError> {
+        let response = self.execute(self.http.head(url)).await?;
+
+        if !response.status().is_success() {
+            return Err(MediaError::Api {
+                status: response.status(),
+                body: String::new(),
+            });
+        }
+
+        Ok(header_content_type(&response)
+            .as_deref()
+            .and_then(MediaKind::from_content_type))
+    }
+
+    /// Downloads the picker item at `index` to `path`.
+    ///
+    /// # Errors
+    /// Returns `MediaError::IndexOutOfRange` if the picker has no item at `index`, or any
+    /// error [`Client::download_with`] can return.
+    pub async fn download_picker_item(
+        &self,
+        picker: &PickerResponse,
+        index: usize,
+        path: impl AsRef<Path>,
+    ) -> Result<DownloadStatus, MediaError> {
+        let item = picker
+            .picker
+            .get(index)
+            .ok_or(MediaError::IndexOutOfRange {
+                index,
+                len: picker.picker.len(),
+            })?;
+
+        self.download_with(&item.url, path, DownloadOptions::default())
+            .await
+    }
+
+    /// Downloads the picker item at `video_index` and the picker's separate audio track
+    /// into `dir`, giving both files the same base name.
+    ///
+    /// Pickers such as TikTok slideshows deliver the audio on its own, to be combined
+    /// with the chosen item by the caller. The base name is taken from the picker's
+    /// `audio_filename`, falling back to `picker`, and the item's index is appended,
+    /// e.g. `sound_2.mp4` and `sound_2.mp3`. If either file already exists, the base
+    /// name is chosen by
+    /// [`ClientBuilder::collision_policy`](super::builder::ClientBuilder::collision_policy).
+    /// Returns the statuses of the item and the audio download, in that order. No
+    /// muxing is done.
+    ///
+    /// # Errors
+    /// Returns `MediaError::IndexOutOfRange` if the picker has no item at `video_index`,
+    /// `MediaError::InvalidRequest` if the picker has no audio track,
+    /// `MediaError::FileExists` if a file exists and the collision policy is
+    /// [`CollisionPolicy::Error`], or any error [`Client::download_with`] can return.
+    pub async fn download_picker_video_with_audio(
+        &self,
+        picker: &PickerResponse,
+        video_index: usize,
+        dir: impl AsRef<Path>,
+    ) -> Result<(DownloadStatus, DownloadStatus), MediaError> {
+        let item = picker
+            .picker
+            .get(video_index)
+            .ok_or(MediaError::IndexOutOfRange {
+                index: video_index,
+                len: picker.picker.len(),
+            })?;
+        let audio = picker.audio.as_deref().ok_or_else(|| {
+            MediaError::InvalidRequest("the picker has no separate audio track".to_string())
+        })?;
+
+        let audio_ext = picker
+            .audio_filename
+            .as_deref()
+            .map(Path::new)
+            .and_then(Path::extension)
+            .and_then(|ext| ext.to_str())
+            .unwrap_or("mp3");
+
+        let video_ext = item.item_type().file_extension();
+        let audio_ext = sanitize_filename(audio_ext);
+        let dir = dir.as_ref();
+        let base = self.free_stem(
+            dir,
+            &picker.item_base_name(video_index),
+            &[Some(video_ext), Some(&audio_ext)],
+            &item.url,
+            &HashSet::new(),
+        )?;
+        let video_path = dir.join(with_extension(&base, Some(video_ext)));
+        let audio_path = dir.join(with_extension(&base, Some(&audio_ext)));
+
+        let video = self
+            .download_with(&item.url, video_path, DownloadOptions::default())
+            .await?;
+        let audio = self
+            .download_with(audio, audio_path, DownloadOptions::default())
+            .await?;
+
+        Ok((video, audio))
+    }
+
+    /// Downloads only the picker's separate audio track into `dir`, e.g. to keep the
+    /// music of a TikTok slideshow.
+    ///
+    /// The file is named after the picker's `audio_filename`, falling back to
+    /// `picker_audio.mp3`. If the file already exists, the name is chosen by
+    /// [`ClientBuilder::collision_policy`](super::builder::ClientBuilder::collision_policy).
+    ///
+    /// # Errors
+    /// Returns `MediaError::InvalidRequest` if the picker has no audio track,
+    /// `MediaError::FileExists` if the file exists and the collision policy is
+    /// [`CollisionPolicy::Error`], or any error [`Client::download_with`] can return.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cobalt_tools::api::client::Client;
+    /// use cobalt_tools::structs::{download::DownloadStatus, media_response::PickerResponse};
+    ///
+    /// async fn save_audio(client: &Client, picker: &PickerResponse) {
+    ///     match client.download_picker_audio(picker, "downloads").await {
+    ///         Ok(DownloadStatus::Downloaded(report)) => println!("Saved {}", report.path.display()),
+    ///         Ok(DownloadStatus::NotModified) => {}
+    ///         Err(err) => eprintln!("Download failed: {}", err),
+    ///     }
+    /// }
+    /// ```
+    pub async fn download_picker_audio(
+        &self,
+        picker: &PickerResponse,
+        dir: impl AsRef<Path>,
+    ) -> Result<DownloadStatus, MediaError> {
+        let audio = picker.audio.as_deref().ok_or_else(|| {
+            MediaError::InvalidRequest("the picker has no separate audio track".to_string())
+        })?;
+
+        let filename = picker
+            .audio_filename
+            .as_deref()
+            .map(sanitize_filename)
+            .unwrap_or_else(|| "picker_audio.mp3".to_string());
+        let filename = Path::new(&filename);
+        let stem = filename
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or("picker_audio");
+        let ext = filename.extension().and_then(|ext| ext.to_str());
+        let dir = dir.as_ref();
+        let stem = self.free_stem(dir, stem, &[ext], audio, &HashSet::new())?;
+
+        self.download_with(
+            audio,
+            dir.join(with_extension(&stem, ext)),
+            DownloadOptions::default(),
+        )
+        .await
+    }
+
+    /// Downloads every picker item into `dir`, named as by
+    /// [`PickerResponse::planned_files`]. Returns the statuses in picker order.
+    ///
+    /// If a file already exists, its name is chosen by
+    /// [`ClientBuilder::collision_policy`](super::builder::ClientBuilder::collision_policy).
+    /// The names are resolved before anything is downloaded, so with
+    /// [`CollisionPolicy::Error`] no file is written if any of them exists.
+    ///
+    /// # Errors
+    /// Returns `MediaError::FileExists` if a file exists and the collision policy is
+    /// [`CollisionPolicy::Error`], or the first error [`Client::download_with`] returns.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cobalt_tools::api::{client::Client, options::CollisionPolicy};
+    /// use cobalt_tools::structs::media_response::PickerResponse;
+    ///
+    /// async fn save_all(picker: &PickerResponse) {
+    ///     let client = Client::builder("your-api-key-here", "http://localhost:9000")
+    ///         .collision_policy(CollisionPolicy::AppendHash)
+    ///         .build()
+    ///         .expect("Failed to build client");
+    ///
+    ///     match client.download_picker(picker, "downloads").await {
+    ///         Ok(statuses) => println!("Downloaded {} items", statuses.len()),
+    ///         Err(err) => eprintln!("Download failed: {}", err),
+    ///     }
+    /// }
+    /// ```
+    pub async fn download_picker(
+        &self,
+        picker: &PickerResponse,
+        dir: impl AsRef<Path>,
+    ) -> Result<Vec<DownloadStatus>, MediaError> {
+        let dir = dir.as_ref();
+        let mut taken = HashSet::new();
+        let mut planned = Vec::with_capacity(picker.picker.len());
+        for (item, path) in picker.planned_files(dir) {
+            let stem = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .unwrap_or("picker");
+            let ext = path.extension().and_then(|ext| ext.to_str());
+            let stem = self.free_stem(dir, stem, &[ext], &item.url, &taken)?;
+            let path = dir.join(with_extension(&stem, ext));
+            taken.insert(path.clone());
+            planned.push((item, path));
+        }
+
+        let mut statuses = Vec::with_capacity(planned.len());
+        for (item, path) in planned {
+            statuses.push(
+                self.download_with(&item.url, path, DownloadOptions::default())
+                    .await?,
+            );
+        }
+
+        Ok(statuses)
+    }
+
+    /// Downloads already resolved tunnel or redirect URLs, each to its path, running up
+    /// to `concurrency` downloads at once. A `concurrency` of 0 is treated as 1.
+    ///
+    /// Returns one result per job, in the order of `jobs`; a failed download doesn't
+    /// stop the others. Each download behaves like [`Client::download_with`] with the
+    /// default options, so existing files are overwritten.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cobalt_tools::api::client::Client;
+    /// use std::path::PathBuf;
+    ///
+    /// async fn save_all(links: Vec<String>) {
+    ///     let client = Client::builder("your-api-key-here", "http://localhost:9000")
+    ///         .build()
+    ///         .expect("Failed to build client");
+    ///
+    ///     let jobs = links
+    ///         .into_iter()
+    ///         .enumerate()
+    ///         .map(|(index, link)| (link, PathBuf::from(format!("downloads/{}.mp4", index))))
+    ///         .collect();
+    ///
+    ///     for result in client.download_all(jobs, 4).await {
+    ///         if let Err(err) = result {
+    ///             eprintln!("Download failed: {}", err);
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub async fn download_all(
+        &self,
+        jobs: Vec<(String, PathBuf)>,
+        concurrency: usize,
+    ) -> Vec<Result<DownloadStatus, MediaError>> {
+        futures_util::stream::iter(jobs)
+            .map(|(url, path)| async move {
+                self.download_with(&url, path, DownloadOptions::default())
+                    .await
+            })
+            .buffered(concurrency.max(1))
+            .collect()
+            .await
+    }
+
+    /// Returns a file stem, based on `stem`, for which no file with any of `exts` exists
+    /// in `dir` or is in `taken`, following the client's collision policy.
+    fn free_stem(
+        &self,
+        dir: &Path,
+        stem: &str,
+        exts: &[Option<&str>],
+        url: &str,
+        taken: &HashSet<PathBuf>,
+    ) -> Result<String, MediaError> {
+        let is_free = |stem: &str| {
+            exts.iter().all(|ext| {
+                let path = dir.join(with_extension(stem, *ext));
+                !path.exists() && !taken.contains(&path)
+            })
+        };
+        if is_free(stem) {
+            return Ok(stem.to_string());
+        }
+
+        let base = match self.collision_policy {
+            CollisionPolicy::Error => {
+                let path = dir.join(with_extension(stem, exts.first().copied().flatten()));
+                return Err(MediaError::FileExists(path.display().to_string()));
+            }
+            CollisionPolicy::AppendIndex => stem.to_string(),
+            CollisionPolicy::AppendHash => {
+                let hashed = format!("{}-{:08x}", stem, fnv1a(url.as_bytes()) as u32);
+                if is_free(&hashed) {
+                    return Ok(hashed);
+                }
+                hashed
+            }
+        };
+
+        Ok((1..)
+            .map(|index| format!("{} ({})", base, index))
+            .find(|candidate| is_free(candidate))
+            .expect("some index is always free"))
+    }
+}
+
+/// Joins a file stem and an optional extension into a file name.
+fn with_extension(stem: &str, ext: Option<&str>) -> String {
+    match ext {
+        Some(ext) => format!("{}.{}", stem, ext),
+        None => stem.to_string(),
+    }
+}
+
+/// What a batch job reports to the task aggregating the batch's progress.
+enum JobUpdate {
+    Started,
+    Progress(Progress),
+    Done(Result<DownloadStatus, String>, Option<Progress>),
+}
+
+/// Updates `overall` with a job's latest `progress`, given the job's last seen bytes
+/// and size in `seen`.
+fn record_progress(overall: &mut BatchProgress, seen: &mut (u64, Option<u64>), progress: Progress) {
+    overall.downloaded = overall.downloaded - seen.0 + progress.downloaded;
+    if seen.1.is_none() {
+        overall.total += progress.total.unwrap_or(0);
+    }
+    *seen = (progress.downloaded, progress.total.or(seen.1));
+}
+
+/// Runs `future`, failing with `MediaError::DeadlineExceeded` once `deadline` has passed.
+pub(crate) async fn before_deadline<F: Future>(
+    deadline: Option<Instant>,
+    future: F,
+) -> Result<F::Output, MediaError> {
+    match deadline {
+        Some(deadline) => tokio::time::timeout_at(deadline.into(), future)
+            .await
+            .map_err(|_| MediaError::DeadlineExceeded),
+        None => Ok(future.await),
+    }
+}
+
+/// Awaits `future`, failing with `MediaError::StalledDownload` if it takes longer than
+/// `idle`.
+async fn before_idle<F: Future>(
+    idle: Option<Duration>,
+    future: F,
+) -> Result<F::Output, MediaError> {
+    match idle {
+        Some(idle) => tokio::time::timeout(idle, future)
+            .await
+            .map_err(|_| MediaError::StalledDownload { idle }),
+        None => Ok(future.await),
+    }
+}
+
+/// The `.part` file of an atomic download, removed when dropped unless committed.
+///
+/// Dropping happens on errors and when the download future itself is dropped, so
+/// cancelled downloads don't leave temporary files behind.
+struct PartFile {
+    path: PathBuf,
+    remove_on_drop: bool,
+}
+
+impl PartFile {
+    fn new(target: &Path, remove_on_drop: bool) -> Self {
+        let mut path = target.as_os_str().to_owned();
+        path.push(".part");
+
+        PartFile {
+            path: PathBuf::from(path),
+            remove_on_drop,
+        }
+    }
+
+    fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Moves the finished file to `target`.
+    fn commit(mut self, target: &Path) -> io::Result<()> {
+        fs::rename(&self.path, target)?;
+        self.remove_on_drop = false;
+        Ok(())
+    }
+}
+
+impl Drop for PartFile {
+    fn drop(&mut self) {
+        if self.remove_on_drop {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+/// Returns the earlier of two optional deadlines.
+pub(crate) fn earliest(a: Option<Instant>, b: Option<Instant>) -> Option<Instant> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+fn header_etag(response: &Response) -> Option<String> {
+    response
+        .headers()
+        .get(ETAG)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string)
+}
+
+fn header_content_type(response: &Response) -> Option<String> {
+    response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string)
+}
+
+/// Parses `Content-Range` into the first byte sent and the full size, when known.
+///
+/// `bytes 100-999/1000` yields `(Some(100), Some(1000))` and `bytes */1000`, sent with
+/// `416 Range Not Satisfiable`, yields `(None, Some(1000))`.
+fn content_range(response: &Response) -> Option<(Option<u64>, Option<u64>)> {
+    let value = response.headers().get(CONTENT_RANGE)?.to_str().ok()?;
+    let (range, size) = value.strip_prefix("bytes ")?.split_once('/')?;
+
+    let start = match range {
+        "*" => None,
+        _ => Some(range.split_once('-')?.0.parse().ok()?),
+    };
+
+    Some((start, size.parse().ok()))
+}
+
+/// Checks that a `206 Partial Content` answer continues the same remote file at `existing`.
+fn resume_matches(response: &Response, existing: u64, options: &DownloadOptions<'_>) -> bool {
+    let Some((Some(start), size)) = content_range(response) else {
+        return false;
+    };
+
+    let size_matches = match (options.expected_size, size) {
+        (Some(expected), Some(size)) => expected == size,
+        _ => true,
+    };
+    let etag_matches = match (options.resume_etag, header_etag(response)) {
+        (Some(expected), Some(etag)) => expected == etag,
+        _ => true,
+    };
+
+    start == existing && size_matches && etag_matches
+}
+
+/// Smooths transfer speed with an exponential moving average.
+///
+/// Chunks are accumulated into samples of at least [`SpeedMeter::SAMPLE_INTERVAL`], since
+/// chunks arriving back to back would otherwise produce wildly spiking rates.
+struct SpeedMeter<'a> {
+    clock: &'a dyn Clock,
+    sample_start: Instant,
+    sample_bytes: u64,
+    speed: Option<f64>,
+}
+
+impl<'a> SpeedMeter<'a> {
+    const SAMPLE_INTERVAL: Duration = Duration::from_millis(100);
+    const SMOOTHING: f64 = 0.3;
+
+    fn new(clock: &'a dyn Clock) -> Self {
+        SpeedMeter {
+            clock,
+            sample_start: clock.now(),
+            sample_bytes: 0,
+            speed: None,
+        }
+    }
+
+    /// Records `bytes` received now and returns the current speed in bytes per second.
+    fn record(&mut self, bytes: u64) -> f64 {
+        self.sample_bytes += bytes;
+
+        let now = self.clock.now();
+        let elapsed = now.saturating_duration_since(self.sample_start);
+        if elapsed >= Self::SAMPLE_INTERVAL {
+            let current = self.sample_bytes as f64 / elapsed.as_secs_f64();
+            self.speed = Some(match self.speed {
+                Some(speed) => Self::SMOOTHING * current + (1.0 - Self::SMOOTHING) * speed,
+                None => current,
+            });
+
+            self.sample_start = now;
+            self.sample_bytes = 0;
+        }
+
+        self.speed.unwrap_or(0.0)
+    }
+}
@@ -0,0 +1,38 @@
+use reqwest::{Request, Response};
+
+/// A hook that observes every request the client sends and every response it receives.
+///
+/// Set one with [`ClientBuilder::interceptor`](super::builder::ClientBuilder::interceptor)
+/// for custom logging or auditing. Both methods default to doing nothing. Requests to the
+/// instance carry the API key in their `Authorization` header, so redact it before
+/// writing requests anywhere.
+///
+/// # Example
+/// ```rust
+/// use cobalt_tools::api::{client::Client, interceptor::Interceptor};
+/// use std::sync::Arc;
+///
+/// struct Logger;
+///
+/// impl Interceptor for Logger {
+///     fn before_request(&self, request: &reqwest::Request) {
+///         println!("-> {} {}", request.method(), request.url());
+///     }
+///
+///     fn after_response(&self, response: &reqwest::Response) {
+///         println!("<- {} {}", response.status(), response.url());
+///     }
+/// }
+///
+/// let client = Client::builder("your-api-key-here", "http://localhost:9000")
+///     .interceptor(Arc::new(Logger))
+///     .build()
+///     .expect("Failed to build client");
+/// ```
+pub trait Interceptor: Send + Sync {
+    /// Called right before `request` is sent.
+    fn before_request(&self, _request: &Request) {}
+
+    /// Called once the response headers have been received, before the body is read.
+    fn after_response(&self, _response: &Response) {}
+}
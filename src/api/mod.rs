@@ -1,3 +1,16 @@
+#[cfg(feature = "blocking")]
+pub mod blocking;
+pub mod builder;
 pub mod client;
+pub mod clock;
+pub mod config;
+mod download;
+pub mod interceptor;
+pub mod multi;
+pub mod options;
+#[cfg(feature = "otel")]
+pub mod otel;
+pub mod retry;
+mod tls;
 
 pub use client::CLIENT_INSTANCE as CobaltClient;
\ No newline at end of file
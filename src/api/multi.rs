@@ -0,0 +1,251 @@
+use futures_util::StreamExt;
+use std::env;
+
+use crate::structs::{
+    client_init_error::ClientInitError, media_error::MediaError, media_request::MediaRequestData,
+    media_response::Response, service::service_for_url, StatusResponse,
+};
+
+use super::{
+    builder::ClientBuilder,
+    client::{self, Client},
+    options::RequestOptions,
+};
+
+/// How many instances [`MultiClient::status_all`] queries at once.
+const STATUS_CONCURRENCY: usize = 8;
+
+/// What [`MultiClient::from_env`] does with malformed entries in `INSTANCE_URIS`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum InvalidUriPolicy {
+    /// Fails with the first malformed URI.
+    #[default]
+    Error,
+    /// Leaves malformed URIs out of the pool.
+    Skip,
+}
+
+/// A pool of clients for several instances, tried in order.
+///
+/// Requests go to the first instance; network errors, empty responses and HTTP 5xx or
+/// 429 answers move on to the next one. Any other error is returned right away.
+///
+/// # Example
+/// ```rust
+/// use cobalt_tools::api::{client::Client, multi::MultiClient};
+///
+/// let clients = ["http://localhost:9000", "http://localhost:9001"]
+///     .into_iter()
+///     .map(|uri| Client::builder("your-api-key-here", uri).build())
+///     .collect::<Result<Vec<_>, _>>()
+///     .expect("Failed to build clients");
+///
+/// let multi = MultiClient::new(clients);
+/// assert_eq!(multi.clients().len(), 2);
+/// ```
+#[derive(Clone)]
+pub struct MultiClient {
+    clients: Vec<Client>,
+}
+
+impl MultiClient {
+    /// Creates a pool that tries `clients` in the given order.
+    pub fn new(clients: Vec<Client>) -> Self {
+        MultiClient { clients }
+    }
+
+    /// Creates a pool from the `API_KEY` and `INSTANCE_URIS` environment variables.
+    ///
+    /// `INSTANCE_URIS` is a comma-separated list of instance URIs sharing `API_KEY`. If
+    /// it's not set, the single `INSTANCE_URI` is used, so existing configurations keep
+    /// working.
+    ///
+    /// # Errors
+    /// Returns `ClientInitError::MissingVar` if a variable is missing or the list holds
+    /// no usable URI, `ClientInitError::InvalidUri` for a malformed URI under
+    /// [`InvalidUriPolicy::Error`], or `ClientInitError::HttpClient` if an HTTP client
+    /// cannot be initialized.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cobalt_tools::api::multi::{InvalidUriPolicy, MultiClient};
+    ///
+    /// std::env::set_var("API_KEY", "your-api-key-here");
+    /// std::env::set_var("INSTANCE_URIS", "http://localhost:9000, http://localhost:9001");
+    ///
+    /// let multi = MultiClient::from_env(InvalidUriPolicy::Skip).expect("Invalid configuration");
+    /// ```
+    pub fn from_env(policy: InvalidUriPolicy) -> Result<Self, ClientInitError> {
+        let api_key = env::var("API_KEY").map_err(|_| ClientInitError::MissingVar("API_KEY"))?;
+        let instance_uris = env::var("INSTANCE_URIS")
+            .or_else(|_| env::var("INSTANCE_URI"))
+            .map_err(|_| ClientInitError::MissingVar("INSTANCE_URIS"))?;
+
+        let clients = parse_instance_uris(&instance_uris, policy)?
+            .into_iter()
+            .map(|uri| {
+                ClientBuilder::new(api_key.clone(), uri)
+                    .build()
+                    .map_err(ClientInitError::from_build_error)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(MultiClient::new(clients))
+    }
+
+    /// Returns the clients in the order they are tried.
+    pub fn clients(&self) -> &[Client] {
+        &self.clients
+    }
+
+    /// Returns the first client whose instance lists the service hosting `url`.
+    ///
+    /// Statuses come from each client's [`Client::cached_status`], so they are only
+    /// refetched once their cache expires. Instances whose status can't be fetched are
+    /// skipped. Returns `None` if no instance supports the service, or if the service
+    /// isn't known to [`service_for_url`].
+    pub async fn client_for_url(&self, url: &str) -> Option<&Client> {
+        let service = service_for_url(url)?;
+
+        for client in &self.clients {
+            let Ok(status) = client.cached_status().await else {
+                continue;
+            };
+            if status.cobalt.services.iter().any(|name| name == service) {
+                return Some(client);
+            }
+        }
+
+        None
+    }
+
+    /// Returns the URI of the instance [`MultiClient::client_for_url`] picks for `url`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cobalt_tools::api::{client::Client, multi::MultiClient};
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let clients = ["http://localhost:9000", "http://localhost:9001"]
+    ///         .into_iter()
+    ///         .map(|uri| Client::builder("your-api-key-here", uri).build())
+    ///         .collect::<Result<Vec<_>, _>>()
+    ///         .expect("Failed to build clients");
+    ///     let multi = MultiClient::new(clients);
+    ///
+    ///     match multi.instance_for_url("https://www.youtube.com/watch?v=1lML-Uem6Ns").await {
+    ///         Some(instance_uri) => println!("Using {}", instance_uri),
+    ///         None => eprintln!("No instance supports youtube"),
+    ///     }
+    /// }
+    /// ```
+    pub async fn instance_for_url(&self, url: &str) -> Option<&str> {
+        self.client_for_url(url).await.map(Client::instance_uri)
+    }
+
+    /// Fetches the status of every instance, a few at a time, returning each instance URI
+    /// with its result in pool order.
+    ///
+    /// Each status is fetched fresh with [`Client::verify_instance`], bypassing the
+    /// status cache, so the report reflects the pool right now.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cobalt_tools::api::{client::Client, multi::MultiClient};
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let clients = ["http://localhost:9000", "http://localhost:9001"]
+    ///         .into_iter()
+    ///         .map(|uri| Client::builder("your-api-key-here", uri).build())
+    ///         .collect::<Result<Vec<_>, _>>()
+    ///         .expect("Failed to build clients");
+    ///     let multi = MultiClient::new(clients);
+    ///
+    ///     for (instance_uri, status) in multi.status_all().await {
+    ///         match status {
+    ///             Ok(status) => println!("{}: cobalt {}", instance_uri, status.cobalt.version),
+    ///             Err(err) => eprintln!("{}: {}", instance_uri, err),
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub async fn status_all(&self) -> Vec<(String, Result<StatusResponse, MediaError>)> {
+        futures_util::stream::iter(&self.clients)
+            .map(|client| async move {
+                (
+                    client.instance_uri().to_string(),
+                    client.verify_instance().await,
+                )
+            })
+            .buffered(STATUS_CONCURRENCY)
+            .collect()
+            .await
+    }
+
+    /// Fetches media like [`Client::get_media`], failing over between instances.
+    pub async fn get_media(
+        &self,
+        override_api_key: Option<String>,
+        video_data: MediaRequestData<'_>,
+    ) -> Result<Response, MediaError> {
+        let options = RequestOptions {
+            api_key: override_api_key.as_deref(),
+            ..Default::default()
+        };
+
+        self.get_media_with(options, video_data).await
+    }
+
+    /// Fetches media like [`Client::get_media_with`], failing over between instances.
+    ///
+    /// # Errors
+    /// Returns the last instance's error if every instance failed, or
+    /// `MediaError::InvalidInstance` if the pool is empty.
+    pub async fn get_media_with(
+        &self,
+        options: RequestOptions<'_>,
+        video_data: MediaRequestData<'_>,
+    ) -> Result<Response, MediaError> {
+        let mut last_error = MediaError::InvalidInstance("no instances are configured".to_string());
+
+        for client in &self.clients {
+            match client
+                .get_media_with(options.clone(), video_data.clone())
+                .await
+            {
+                Err(error) if error.is_retryable() => last_error = error,
+                result => return result,
+            }
+        }
+
+        Err(last_error)
+    }
+}
+
+/// Splits a comma-separated list of instance URIs, checking each one.
+pub(crate) fn parse_instance_uris(
+    value: &str,
+    policy: InvalidUriPolicy,
+) -> Result<Vec<String>, ClientInitError> {
+    let mut uris = Vec::new();
+
+    for uri in value
+        .split(',')
+        .map(str::trim)
+        .filter(|uri| !uri.is_empty())
+    {
+        match (client::check_instance_uri(uri), policy) {
+            (Ok(()), _) => uris.push(uri.to_string()),
+            (Err(_), InvalidUriPolicy::Skip) => {}
+            (Err(error), InvalidUriPolicy::Error) => return Err(error),
+        }
+    }
+
+    if uris.is_empty() {
+        return Err(ClientInitError::MissingVar("INSTANCE_URIS"));
+    }
+
+    Ok(uris)
+}
@@ -0,0 +1,171 @@
+use std::time::{Duration, Instant};
+
+/// Per-call overrides for a single media request.
+///
+/// Any field left as `None` falls back to the value configured on the [`Client`](super::client::Client).
+///
+/// # Example
+/// ```rust
+/// use cobalt_tools::api::options::RequestOptions;
+///
+/// let options = RequestOptions {
+///     content_type: Some("application/json; charset=utf-8"),
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct RequestOptions<'a> {
+    /// Overrides the client's API key.
+    pub api_key: Option<&'a str>,
+    /// Overrides the client's default `Accept` header.
+    pub accept: Option<&'a str>,
+    /// Overrides the client's default `Content-Type` header.
+    pub content_type: Option<&'a str>,
+    /// Sent as the `X-Request-Id` header, for matching client and instance logs.
+    ///
+    /// When unset, an ID is generated if the client was built with
+    /// [`ClientBuilder::request_ids`](super::builder::ClientBuilder::request_ids).
+    pub request_id: Option<&'a str>,
+    /// Query parameters appended to the instance URI, after those set with
+    /// [`ClientBuilder::query_param`](super::builder::ClientBuilder::query_param).
+    pub query: &'a [(&'a str, &'a str)],
+}
+
+/// Per-call settings for [`Client::download_with`](super::client::Client::download_with).
+///
+/// # Example
+/// ```rust
+/// use cobalt_tools::api::options::DownloadOptions;
+///
+/// let options = DownloadOptions {
+///     if_none_match: Some("\"5d8c72a5edda8d6a\""),
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct DownloadOptions<'a> {
+    /// An ETag from a previous download, sent as `If-None-Match`.
+    ///
+    /// If the server answers `304 Not Modified`, nothing is written to disk.
+    pub if_none_match: Option<&'a str>,
+    /// Continues a partial file at the target path with a `Range` request instead of
+    /// starting over.
+    ///
+    /// The server's `Content-Range` must continue exactly where the file ends, and must
+    /// match `expected_size` and `resume_etag` when they are set. If the remote file
+    /// looks different, or the server ignores the range, the download restarts from zero.
+    pub resume: bool,
+    /// The ETag of the earlier, interrupted download, sent as `If-Range` when resuming.
+    pub resume_etag: Option<&'a str>,
+    /// The full size of the file in bytes, checked against `Content-Range` when resuming.
+    pub expected_size: Option<u64>,
+    /// A wall-clock limit for the whole download, including any restart after a failed
+    /// resume. Once it passes, the download fails with `MediaError::DeadlineExceeded`.
+    ///
+    /// Unlike [`ClientBuilder::timeout`](super::builder::ClientBuilder::timeout), which
+    /// bounds each request on its own, this bounds the operation as a whole.
+    pub deadline: Option<Instant>,
+    /// A time limit for the download derived from the file, applied alongside
+    /// `deadline`. Unset by default, leaving only `deadline` and the client's timeout.
+    pub timeout: Option<TimeoutPolicy>,
+    /// The longest the download may wait for the next chunk of the body. Once it passes,
+    /// the download fails with `MediaError::StalledDownload`.
+    ///
+    /// This catches connections the server stopped sending on without closing them,
+    /// which `deadline` and `timeout` only notice once the whole download has run out of
+    /// time.
+    pub idle_timeout: Option<Duration>,
+    /// Accepts responses that report `Content-Length: 0`.
+    ///
+    /// By default such a response fails with `MediaError::EmptyResponse` before the
+    /// target file is touched. Some instances send that header on streamed media, so
+    /// setting this writes whatever bytes actually arrive instead.
+    pub allow_empty: bool,
+    /// Downloads into `<path>.part` and renames it to `path` only once the download
+    /// has finished, so `path` never holds a partial file.
+    ///
+    /// If the download fails or its future is dropped, the `.part` file is removed,
+    /// unless `resume` is set, in which case it is kept and continued next time.
+    pub atomic: bool,
+}
+
+/// How long a download may take, see [`DownloadOptions::timeout`].
+///
+/// When the limit passes, the download fails with `MediaError::DeadlineExceeded`.
+///
+/// # Example
+/// ```rust
+/// use cobalt_tools::api::options::{DownloadOptions, TimeoutPolicy};
+/// use std::time::Duration;
+///
+/// // At least 100 kB/s, but never less than 10 seconds.
+/// let options = DownloadOptions {
+///     timeout: Some(TimeoutPolicy::PerByte {
+///         min_bytes_per_sec: 100_000,
+///         min_timeout: Duration::from_secs(10),
+///     }),
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeoutPolicy {
+    /// The same limit for every download, counted from the start of the call.
+    Fixed(Duration),
+    /// A limit that grows with the file's `Content-Length`, so small files fail fast
+    /// while large ones get the time they need. Without a `Content-Length`, no limit
+    /// is applied.
+    PerByte {
+        /// The slowest acceptable average transfer rate.
+        min_bytes_per_sec: u64,
+        /// The limit for files so small that the rate alone would allow almost no time.
+        min_timeout: Duration,
+    },
+}
+
+impl TimeoutPolicy {
+    /// Returns the time a download of `size` bytes may take, if limited.
+    ///
+    /// A limit too long for a [`Duration`], e.g. for a bogus `Content-Length`, is
+    /// capped at [`Duration::MAX`].
+    pub fn limit(&self, size: Option<u64>) -> Option<Duration> {
+        match *self {
+            TimeoutPolicy::Fixed(limit) => Some(limit),
+            TimeoutPolicy::PerByte {
+                min_bytes_per_sec,
+                min_timeout,
+            } => {
+                let size = size?;
+                let limit =
+                    Duration::try_from_secs_f64(size as f64 / min_bytes_per_sec.max(1) as f64)
+                        .unwrap_or(Duration::MAX);
+                Some(limit.max(min_timeout))
+            }
+        }
+    }
+}
+
+/// How a batch reacts when one of its jobs fails.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum BatchMode {
+    /// Runs every job and reports each failure.
+    #[default]
+    CollectAll,
+    /// Stops the whole batch at the first failure. Jobs in flight are cancelled and
+    /// jobs that haven't started are skipped.
+    FailFast,
+}
+
+/// What the directory download helpers do when a file they would write already exists.
+///
+/// Set with [`ClientBuilder::collision_policy`](super::builder::ClientBuilder::collision_policy).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum CollisionPolicy {
+    /// Appends the first free index, e.g. `video (1).mp4`.
+    #[default]
+    AppendIndex,
+    /// Appends a short hash of the download URL, e.g. `video-3f2a9c1e.mp4`, so the same
+    /// media maps to the same name.
+    AppendHash,
+    /// Fails with `MediaError::FileExists`.
+    Error,
+}
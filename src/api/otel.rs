@@ -0,0 +1,116 @@
+use reqwest::{
+    header::{HeaderMap, HeaderName, HeaderValue},
+    Request, Response,
+};
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+};
+use tracing::{field::Empty, Span};
+
+use crate::structs::media_error::MediaError;
+
+/// Creates a client span for `request`, named and attributed after the OpenTelemetry
+/// HTTP semantic conventions, so `tracing-opentelemetry` exports it as an HTTP client
+/// span.
+///
+/// Headers are never recorded, so the API key in `Authorization` can't leak into
+/// traces, and credentials in the URL are removed.
+pub(crate) fn request_span(request: &Request) -> Span {
+    let mut url = request.url().clone();
+    let _ = url.set_username("");
+    let _ = url.set_password(None);
+
+    tracing::info_span!(
+        "HTTP request",
+        otel.name = %request.method(),
+        otel.kind = "client",
+        otel.status_code = Empty,
+        http.request.method = %request.method(),
+        url.full = %url,
+        server.address = url.host_str().unwrap_or_default(),
+        server.port = url.port_or_known_default(),
+        http.response.status_code = Empty,
+        error.type = Empty,
+        trace_id = Empty,
+    )
+}
+
+/// Records the outcome of the request `span` was created for.
+pub(crate) fn record_response(span: &Span, response: &Result<Response, MediaError>) {
+    match response {
+        Ok(response) => {
+            let status = response.status();
+            span.record("http.response.status_code", status.as_u16());
+            if status.is_client_error() || status.is_server_error() {
+                span.record("otel.status_code", "ERROR");
+                span.record("error.type", status.as_str());
+            }
+        }
+        Err(_) => {
+            span.record("otel.status_code", "ERROR");
+            span.record("error.type", "_OTHER");
+        }
+    }
+}
+
+/// The W3C Trace Context header naming the trace and parent span of a request.
+pub const TRACEPARENT: HeaderName = HeaderName::from_static("traceparent");
+
+/// Writes trace context into the headers of every outgoing request, so the instance's
+/// traces join the caller's.
+///
+/// Set one with [`ClientBuilder::propagator`](super::builder::ClientBuilder::propagator).
+/// `inject` is called with the request's HTTP client span entered, so
+/// [`Span::current`] is the parent the instance should continue from. The default,
+/// [`W3CTraceContext`], starts a new trace for every request, since `tracing` spans carry
+/// no trace ids of their own. To continue an OpenTelemetry trace instead, delegate to
+/// its propagator, with `tracing-opentelemetry` and `opentelemetry-http`:
+///
+/// ```rust,ignore
+/// use cobalt_tools::api::otel::TracePropagator;
+/// use opentelemetry::global;
+/// use opentelemetry_http::HeaderInjector;
+/// use tracing_opentelemetry::OpenTelemetrySpanExt;
+///
+/// struct OtelPropagator;
+///
+/// impl TracePropagator for OtelPropagator {
+///     fn inject(&self, headers: &mut reqwest::header::HeaderMap) {
+///         let context = tracing::Span::current().context();
+///         global::get_text_map_propagator(|propagator| {
+///             propagator.inject_context(&context, &mut HeaderInjector(headers))
+///         });
+///     }
+/// }
+/// ```
+pub trait TracePropagator: Send + Sync {
+    fn inject(&self, headers: &mut HeaderMap);
+}
+
+/// Sends a W3C `traceparent` header with a new, sampled trace for every request that
+/// doesn't carry one yet, and records its trace id on the request span.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct W3CTraceContext;
+
+impl TracePropagator for W3CTraceContext {
+    fn inject(&self, headers: &mut HeaderMap) {
+        if headers.contains_key(TRACEPARENT) {
+            return;
+        }
+
+        let trace_id = format!("{:016x}{:016x}", random_nonzero(0), random_nonzero(1));
+        let traceparent = format!("00-{}-{:016x}-01", trace_id, random_nonzero(2));
+        Span::current().record("trace_id", trace_id.as_str());
+        if let Ok(value) = HeaderValue::from_str(&traceparent) {
+            headers.insert(TRACEPARENT, value);
+        }
+    }
+}
+
+/// Returns a random non-zero number, as W3C trace and span ids must not be all zeros.
+fn random_nonzero(salt: u8) -> u64 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u8(salt);
+    hasher.finish().max(1)
+}
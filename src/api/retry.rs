@@ -0,0 +1,563 @@
+use std::{
+    collections::hash_map::RandomState,
+    future::Future,
+    hash::{BuildHasher, Hasher},
+    path::Path,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use crate::structs::{
+    download::DownloadStatus, media_error::MediaError, media_request::MediaRequestData,
+    media_response::Response, StatusResponse,
+};
+
+use super::{
+    client::Client,
+    clock::Clock,
+    download::{before_deadline, earliest},
+    options::{DownloadOptions, RequestOptions},
+};
+
+/// The number of attempts, including the first one, made by the default backoffs.
+pub const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+
+/// The initial delay used by the default backoffs.
+pub const DEFAULT_BASE_DELAY: Duration = Duration::from_millis(500);
+
+/// The longest delay [`Exponential::new`] waits between attempts.
+pub const DEFAULT_MAX_DELAY: Duration = Duration::from_secs(30);
+
+/// The first delay between the status requests of [`Client::wait_until_ready`].
+const READY_BASE_DELAY: Duration = Duration::from_millis(100);
+
+/// The longest delay between the status requests of [`Client::wait_until_ready`].
+const READY_MAX_DELAY: Duration = Duration::from_secs(2);
+
+/// Decides how long to wait before retrying a failed operation.
+///
+/// `attempt` is the number of attempts made so far, starting at 1 after the first failure.
+/// Returning `None` stops retrying, and the last error is returned.
+pub trait Backoff {
+    fn next_delay(&mut self, attempt: u32) -> Option<Duration>;
+}
+
+/// Waits the same delay between attempts.
+#[derive(Debug, Clone)]
+pub struct Fixed {
+    pub delay: Duration,
+    /// The total number of attempts, including the first one.
+    pub max_attempts: u32,
+}
+
+impl Fixed {
+    pub fn new(delay: Duration, max_attempts: u32) -> Self {
+        Fixed {
+            delay,
+            max_attempts,
+        }
+    }
+}
+
+impl Default for Fixed {
+    fn default() -> Self {
+        Fixed::new(DEFAULT_BASE_DELAY, DEFAULT_MAX_ATTEMPTS)
+    }
+}
+
+impl Backoff for Fixed {
+    fn next_delay(&mut self, attempt: u32) -> Option<Duration> {
+        (attempt < self.max_attempts).then_some(self.delay)
+    }
+}
+
+/// Doubles the delay after every attempt, starting at `base` and capped at `max_delay`.
+#[derive(Debug, Clone)]
+pub struct Exponential {
+    pub base: Duration,
+    pub max_delay: Duration,
+    /// The total number of attempts, including the first one.
+    pub max_attempts: u32,
+}
+
+impl Exponential {
+    pub fn new(base: Duration, max_attempts: u32) -> Self {
+        Exponential {
+            base,
+            max_delay: DEFAULT_MAX_DELAY,
+            max_attempts,
+        }
+    }
+
+    fn delay(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.base.saturating_mul(factor).min(self.max_delay)
+    }
+}
+
+impl Default for Exponential {
+    fn default() -> Self {
+        Exponential::new(DEFAULT_BASE_DELAY, DEFAULT_MAX_ATTEMPTS)
+    }
+}
+
+impl Backoff for Exponential {
+    fn next_delay(&mut self, attempt: u32) -> Option<Duration> {
+        (attempt < self.max_attempts).then(|| self.delay(attempt))
+    }
+}
+
+/// Like [`Exponential`], but waits a random delay between zero and the exponential one.
+///
+/// Spreading retries out this way ("full jitter") keeps many clients that failed at the
+/// same moment from retrying in lockstep.
+#[derive(Debug, Clone, Default)]
+pub struct ExponentialJitter {
+    pub exponential: Exponential,
+}
+
+impl ExponentialJitter {
+    pub fn new(base: Duration, max_attempts: u32) -> Self {
+        ExponentialJitter {
+            exponential: Exponential::new(base, max_attempts),
+        }
+    }
+}
+
+impl Backoff for ExponentialJitter {
+    fn next_delay(&mut self, attempt: u32) -> Option<Duration> {
+        let delay = self.exponential.next_delay(attempt)?;
+        let random = RandomState::new().build_hasher().finish();
+
+        Some(delay.mul_f64((random as f64) / (u64::MAX as f64)))
+    }
+}
+
+/// Decides whether a failed operation is retried.
+///
+/// `attempt` is the number of attempts made so far, starting at 1 after the first failure.
+/// The [`Backoff`] still decides how long to wait, and can stop retrying on its own.
+/// Closures taking the attempt and the error implement this trait.
+///
+/// # Example
+/// ```rust
+/// use cobalt_tools::api::retry::{DefaultRetryPolicy, RetryPolicy};
+/// use cobalt_tools::structs::media_error::MediaError;
+///
+/// // Also retries cobalt errors reported with HTTP 400, once.
+/// let policy = |attempt: u32, error: &MediaError| {
+///     DefaultRetryPolicy.should_retry(attempt, error)
+///         || (attempt == 1
+///             && matches!(error, MediaError::Api { status, .. } if status.as_u16() == 400))
+/// };
+/// assert!(!policy.should_retry(1, &MediaError::DeadlineExceeded));
+/// ```
+pub trait RetryPolicy {
+    fn should_retry(&self, attempt: u32, error: &MediaError) -> bool;
+}
+
+impl<F: Fn(u32, &MediaError) -> bool> RetryPolicy for F {
+    fn should_retry(&self, attempt: u32, error: &MediaError) -> bool {
+        self(attempt, error)
+    }
+}
+
+/// Retries network errors, empty responses, stalled downloads, and HTTP 5xx and 429
+/// answers, but not other HTTP 4xx answers or errors that repeating can't fix.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefaultRetryPolicy;
+
+impl RetryPolicy for DefaultRetryPolicy {
+    fn should_retry(&self, _attempt: u32, error: &MediaError) -> bool {
+        error.is_retryable()
+    }
+}
+
+/// Retry settings shared by [`Client::get_media_retrying`] and
+/// [`Client::download_retrying`], so fetching and downloading retry the same way.
+///
+/// The delays grow exponentially from `base_delay`, as with [`Exponential`]. The client
+/// holds a default set with
+/// [`ClientBuilder::retry`](super::builder::ClientBuilder::retry), which
+/// [`Client::get_media_retry_config`] and [`Client::download_retry_config`] replace for a
+/// single call.
+///
+/// # Example
+/// ```rust
+/// use cobalt_tools::api::{client::Client, retry::RetryConfig};
+/// use std::time::Duration;
+///
+/// let retry = RetryConfig::new()
+///     .max_attempts(5)
+///     .base_delay(Duration::from_millis(200))
+///     .jitter(true)
+///     .deadline(Duration::from_secs(60));
+///
+/// let client = Client::builder("your-api-key-here", "http://localhost:9000")
+///     .retry(retry)
+///     .build()
+///     .expect("Failed to build client");
+/// ```
+#[derive(Clone)]
+pub struct RetryConfig {
+    max_attempts: u32,
+    base_delay: Duration,
+    max_delay: Duration,
+    jitter: bool,
+    deadline: Option<Duration>,
+    policy: Arc<dyn RetryPolicy + Send + Sync>,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        RetryConfig {
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            base_delay: DEFAULT_BASE_DELAY,
+            max_delay: DEFAULT_MAX_DELAY,
+            jitter: false,
+            deadline: None,
+            policy: Arc::new(DefaultRetryPolicy),
+        }
+    }
+}
+
+impl RetryConfig {
+    /// Creates a config with the defaults: [`DEFAULT_MAX_ATTEMPTS`] attempts starting
+    /// [`DEFAULT_BASE_DELAY`] apart, no jitter, no deadline and [`DefaultRetryPolicy`].
+    pub fn new() -> Self {
+        RetryConfig::default()
+    }
+
+    /// Sets the total number of attempts, including the first one.
+    pub fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    /// Sets the delay before the first retry.
+    pub fn base_delay(mut self, base_delay: Duration) -> Self {
+        self.base_delay = base_delay;
+        self
+    }
+
+    /// Caps the delay between attempts. Defaults to [`DEFAULT_MAX_DELAY`].
+    pub fn max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    /// Waits a random part of each delay, like [`ExponentialJitter`].
+    pub fn jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Limits the whole operation, retries included, to `deadline` from the start of the
+    /// call. Once it passes, the operation fails with `MediaError::DeadlineExceeded`.
+    /// A deadline too far away to represent, like [`Duration::MAX`], is no deadline.
+    pub fn deadline(mut self, deadline: Duration) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Sets which errors are retried. Defaults to [`DefaultRetryPolicy`].
+    pub fn policy(mut self, policy: impl RetryPolicy + Send + Sync + 'static) -> Self {
+        self.policy = Arc::new(policy);
+        self
+    }
+
+    /// Returns a fresh backoff for one operation.
+    fn backoff(&self) -> Box<dyn Backoff + Send> {
+        let exponential = Exponential {
+            base: self.base_delay,
+            max_delay: self.max_delay,
+            max_attempts: self.max_attempts,
+        };
+
+        match self.jitter {
+            true => Box::new(ExponentialJitter { exponential }),
+            false => Box::new(exponential),
+        }
+    }
+}
+
+impl Client {
+    /// Fetches media like [`Client::get_media_with`], retrying transient failures as set
+    /// with [`ClientBuilder::retry`](super::builder::ClientBuilder::retry).
+    ///
+    /// # Example
+    /// ```rust
+    /// use cobalt_tools::api::{client::Client, options::RequestOptions, retry::RetryConfig};
+    /// use cobalt_tools::structs::media_request::MediaRequestData;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = Client::builder("your-api-key-here", "http://localhost:9000")
+    ///         .retry(RetryConfig::new().max_attempts(2))
+    ///         .build()
+    ///         .expect("Failed to build client");
+    ///
+    ///     let video_data = MediaRequestData {
+    ///         url: "https://www.youtube.com/watch?v=1lML-Uem6Ns",
+    ///         ..Default::default()
+    ///     };
+    ///
+    ///     if let Err(media_error) = client
+    ///         .get_media_retrying(RequestOptions::default(), video_data)
+    ///         .await
+    ///     {
+    ///         eprintln!("Media Error: {}", media_error);
+    ///     }
+    /// }
+    /// ```
+    pub async fn get_media_retrying(
+        &self,
+        options: RequestOptions<'_>,
+        video_data: MediaRequestData<'_>,
+    ) -> Result<Response, MediaError> {
+        self.get_media_retry_config(options, video_data, &self.retry)
+            .await
+    }
+
+    /// Like [`Client::get_media_retrying`], with `config` instead of the client's
+    /// retry settings.
+    pub async fn get_media_retry_config(
+        &self,
+        options: RequestOptions<'_>,
+        video_data: MediaRequestData<'_>,
+        config: &RetryConfig,
+    ) -> Result<Response, MediaError> {
+        let deadline = config
+            .deadline
+            .and_then(|limit| self.clock.now().checked_add(limit));
+
+        retry_loop(
+            || {
+                let request = self.get_media_with(options.clone(), video_data.clone());
+                async move { before_deadline(deadline, request).await? }
+            },
+            &mut *config.backoff(),
+            &*config.policy,
+            deadline,
+            &*self.clock,
+        )
+        .await
+    }
+
+    /// Downloads like [`Client::download_with`], retrying transient failures as set with
+    /// [`ClientBuilder::retry`](super::builder::ClientBuilder::retry).
+    ///
+    /// With `options.resume` set, every retry continues the partial file instead of
+    /// starting over. The retry deadline, if any, also applies to each attempt, alongside
+    /// `options.deadline`, and a retry that would start after either is not attempted.
+    pub async fn download_retrying(
+        &self,
+        tunnel_link: &str,
+        path: impl AsRef<Path>,
+        options: DownloadOptions<'_>,
+    ) -> Result<DownloadStatus, MediaError> {
+        self.download_retry_config(tunnel_link, path, options, &self.retry)
+            .await
+    }
+
+    /// Like [`Client::download_retrying`], with `config` instead of the client's retry
+    /// settings.
+    pub async fn download_retry_config(
+        &self,
+        tunnel_link: &str,
+        path: impl AsRef<Path>,
+        options: DownloadOptions<'_>,
+        config: &RetryConfig,
+    ) -> Result<DownloadStatus, MediaError> {
+        let options = DownloadOptions {
+            deadline: earliest(
+                options.deadline,
+                config
+                    .deadline
+                    .and_then(|limit| self.clock.now().checked_add(limit)),
+            ),
+            ..options
+        };
+
+        self.download_retry_inner(
+            tunnel_link,
+            path.as_ref(),
+            options,
+            &mut *config.backoff(),
+            &*config.policy,
+        )
+        .await
+    }
+
+    /// Polls the instance's status until it answers like a cobalt instance, e.g. while
+    /// it starts up next to the application, and returns that status.
+    ///
+    /// Every failure is retried, since an instance that is still starting may refuse
+    /// connections or answer from a proxy, with delays growing from 100ms to 2s. Each
+    /// status request bypasses the [cached status](Client::cached_status).
+    ///
+    /// # Errors
+    /// Returns `MediaError::DeadlineExceeded` if the instance isn't ready within
+    /// `timeout`, or `MediaError::Cancelled` if the client shuts down while waiting.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cobalt_tools::api::client::Client;
+    /// use std::time::Duration;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = Client::builder("your-api-key-here", "http://localhost:9000")
+    ///         .build()
+    ///         .expect("Failed to build client");
+    ///
+    ///     match client.wait_until_ready(Duration::from_millis(200)).await {
+    ///         Ok(status) => println!("cobalt {} is ready", status.cobalt.version),
+    ///         Err(err) => eprintln!("Instance not ready: {}", err),
+    ///     }
+    /// }
+    /// ```
+    pub async fn wait_until_ready(&self, timeout: Duration) -> Result<StatusResponse, MediaError> {
+        let deadline = self.clock.now().checked_add(timeout);
+        let mut backoff = Exponential {
+            base: READY_BASE_DELAY,
+            max_delay: READY_MAX_DELAY,
+            max_attempts: u32::MAX,
+        };
+
+        retry_loop(
+            || async move { before_deadline(deadline, self.verify_instance()).await? },
+            &mut backoff,
+            &|_, error: &MediaError| !matches!(error, MediaError::Cancelled),
+            deadline,
+            &*self.clock,
+        )
+        .await
+    }
+
+    /// Fetches media like [`Client::get_media_with`], retrying transient failures.
+    ///
+    /// Network errors, empty responses, and HTTP 5xx and 429 answers are retried after
+    /// the delay chosen by `backoff`. Other errors, and successful responses carrying a
+    /// cobalt error, are returned right away. The client's [`RetryConfig`] is not used.
+    #[deprecated(note = "use Client::get_media_retrying or Client::get_media_retry_config")]
+    pub async fn get_media_retry(
+        &self,
+        options: RequestOptions<'_>,
+        video_data: MediaRequestData<'_>,
+        backoff: impl Backoff,
+    ) -> Result<Response, MediaError> {
+        #[allow(deprecated)]
+        self.get_media_retry_policy(options, video_data, backoff, DefaultRetryPolicy)
+            .await
+    }
+
+    /// Like [`Client::get_media_retry`], but `policy` decides which errors are retried.
+    #[deprecated(note = "use Client::get_media_retry_config with RetryConfig::policy")]
+    pub async fn get_media_retry_policy(
+        &self,
+        options: RequestOptions<'_>,
+        video_data: MediaRequestData<'_>,
+        mut backoff: impl Backoff,
+        policy: impl RetryPolicy,
+    ) -> Result<Response, MediaError> {
+        retry_loop(
+            || self.get_media_with(options.clone(), video_data.clone()),
+            &mut backoff,
+            &policy,
+            None,
+            &*self.clock,
+        )
+        .await
+    }
+
+    /// Downloads like [`Client::download_with`], retrying transient failures.
+    ///
+    /// With `options.resume` set, every retry continues the partial file instead of
+    /// starting over. A retry that would start after `options.deadline` is not attempted,
+    /// and `MediaError::DeadlineExceeded` is returned instead. The client's
+    /// [`RetryConfig`] is not used.
+    #[deprecated(note = "use Client::download_retrying or Client::download_retry_config")]
+    pub async fn download_retry(
+        &self,
+        tunnel_link: &str,
+        path: impl AsRef<Path>,
+        options: DownloadOptions<'_>,
+        backoff: impl Backoff,
+    ) -> Result<DownloadStatus, MediaError> {
+        #[allow(deprecated)]
+        self.download_retry_policy(tunnel_link, path, options, backoff, DefaultRetryPolicy)
+            .await
+    }
+
+    /// Like [`Client::download_retry`], but `policy` decides which errors are retried.
+    #[deprecated(note = "use Client::download_retry_config with RetryConfig::policy")]
+    pub async fn download_retry_policy(
+        &self,
+        tunnel_link: &str,
+        path: impl AsRef<Path>,
+        options: DownloadOptions<'_>,
+        mut backoff: impl Backoff,
+        policy: impl RetryPolicy,
+    ) -> Result<DownloadStatus, MediaError> {
+        self.download_retry_inner(tunnel_link, path.as_ref(), options, &mut backoff, &policy)
+            .await
+    }
+
+    /// The body of [`Client::download_retry_policy`] and [`Client::download_retry_config`].
+    async fn download_retry_inner(
+        &self,
+        tunnel_link: &str,
+        path: &Path,
+        options: DownloadOptions<'_>,
+        backoff: &mut (impl Backoff + ?Sized),
+        policy: &(impl RetryPolicy + ?Sized),
+    ) -> Result<DownloadStatus, MediaError> {
+        retry_loop(
+            || self.download_with(tunnel_link, path, options.clone()),
+            backoff,
+            policy,
+            options.deadline,
+            &*self.clock,
+        )
+        .await
+    }
+}
+
+/// Runs `operation` until it succeeds, `policy` declines to retry, `backoff` gives up, or
+/// the next attempt would start after `deadline`, which fails with
+/// `MediaError::DeadlineExceeded`. Time is read from and waited on `clock`.
+async fn retry_loop<T, F: Future<Output = Result<T, MediaError>>>(
+    mut operation: impl FnMut() -> F,
+    backoff: &mut (impl Backoff + ?Sized),
+    policy: &(impl RetryPolicy + ?Sized),
+    deadline: Option<Instant>,
+    clock: &dyn Clock,
+) -> Result<T, MediaError> {
+    let mut attempt = 0;
+
+    loop {
+        attempt += 1;
+
+        let error = match operation().await {
+            Err(error) if policy.should_retry(attempt, &error) => error,
+            result => return result,
+        };
+
+        let Some(delay) = backoff.next_delay(attempt) else {
+            return Err(error);
+        };
+
+        // A delay too long for an `Instant` starts the next attempt after any deadline.
+        let too_late = match (deadline, clock.now().checked_add(delay)) {
+            (Some(deadline), Some(next)) => next >= deadline,
+            (Some(_), None) => true,
+            (None, _) => false,
+        };
+        if too_late {
+            return Err(MediaError::DeadlineExceeded);
+        }
+
+        clock.sleep(delay).await;
+    }
+}
@@ -0,0 +1,134 @@
+use reqwest::ClientBuilder as ReqwestClientBuilder;
+
+use crate::structs::{client_init_error::ClientInitError, media_error::MediaError};
+
+#[cfg(feature = "rustls")]
+use once_cell::sync::Lazy;
+
+/// Well-known locations of the system CA bundle on Linux distributions.
+#[cfg(feature = "rustls")]
+const CERT_BUNDLE_PATHS: &[&str] = &[
+    "/etc/ssl/certs/ca-certificates.crt",
+    "/etc/pki/tls/certs/ca-bundle.crt",
+    "/etc/pki/ca-trust/extracted/pem/tls-ca-bundle.pem",
+    "/etc/ssl/ca-bundle.pem",
+    "/etc/ssl/cert.pem",
+];
+
+/// The system root certificates trusted with the `rustls` feature, loaded once per
+/// process.
+#[cfg(feature = "rustls")]
+static ROOT_CERTIFICATES: Lazy<Result<Vec<reqwest::Certificate>, ClientInitError>> =
+    Lazy::new(system_root_certificates);
+
+/// Selects the TLS backend enabled through the crate features.
+///
+/// With the `rustls` feature, the ring crypto provider is used and root certificates are
+/// loaded from `SSL_CERT_FILE`, `SSL_CERT_DIR`, or the system CA bundle, so statically
+/// linked binaries do not depend on OpenSSL. If none are found, the client is still
+/// built; see [`missing_roots`]. Otherwise reqwest's default backend is left in place.
+pub(crate) fn configure(builder: ReqwestClientBuilder) -> ReqwestClientBuilder {
+    #[cfg(feature = "rustls")]
+    {
+        let _ = rustls::crypto::ring::default_provider().install_default();
+
+        let mut builder = builder.use_rustls_tls();
+        for certificate in ROOT_CERTIFICATES.iter().flatten() {
+            builder = builder.add_root_certificate(certificate.clone());
+        }
+
+        builder
+    }
+
+    #[cfg(not(feature = "rustls"))]
+    {
+        builder
+    }
+}
+
+/// Selects the TLS backend for reqwest's blocking client, mirroring [`configure`].
+#[cfg(feature = "blocking")]
+pub(crate) fn configure_blocking(
+    builder: reqwest::blocking::ClientBuilder,
+) -> reqwest::blocking::ClientBuilder {
+    #[cfg(feature = "rustls")]
+    {
+        let _ = rustls::crypto::ring::default_provider().install_default();
+
+        let mut builder = builder.use_rustls_tls();
+        for certificate in ROOT_CERTIFICATES.iter().flatten() {
+            builder = builder.add_root_certificate(certificate.clone());
+        }
+
+        builder
+    }
+
+    #[cfg(not(feature = "rustls"))]
+    {
+        builder
+    }
+}
+
+/// Returns why HTTPS requests can't succeed, if the `rustls` feature found no system
+/// root certificates and `extra_roots` is `false`.
+///
+/// Clients check this before sending an HTTPS request, so they fail with a clear error
+/// instead of an opaque handshake failure, while plain HTTP keeps working.
+pub(crate) fn missing_roots(extra_roots: bool) -> Option<ClientInitError> {
+    #[cfg(feature = "rustls")]
+    {
+        match &*ROOT_CERTIFICATES {
+            Err(error) if !extra_roots => Some(error.clone()),
+            _ => None,
+        }
+    }
+
+    #[cfg(not(feature = "rustls"))]
+    {
+        let _ = extra_roots;
+        None
+    }
+}
+
+/// Fails with `error`, as returned by [`missing_roots`], if `url` is an HTTPS URL.
+pub(crate) fn check_https(error: Option<&ClientInitError>, url: &str) -> Result<(), MediaError> {
+    match error {
+        Some(error) if reqwest::Url::parse(url).is_ok_and(|url| url.scheme() == "https") => {
+            Err(MediaError::ClientInit(error.clone()))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Reads the first CA bundle found, or else the certificates in the CA directory.
+#[cfg(feature = "rustls")]
+fn system_root_certificates() -> Result<Vec<reqwest::Certificate>, ClientInitError> {
+    use std::path::PathBuf;
+
+    let probe = openssl_probe::probe();
+    let bundle = probe
+        .cert_file
+        .into_iter()
+        .chain(CERT_BUNDLE_PATHS.iter().map(PathBuf::from))
+        .find_map(|path| std::fs::read(path).ok());
+    let certificates = match bundle {
+        Some(bundle) => reqwest::Certificate::from_pem_bundle(&bundle).unwrap_or_default(),
+        None => probe
+            .cert_dir
+            .and_then(|dir| std::fs::read_dir(dir).ok())
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| std::fs::read(entry.ok()?.path()).ok())
+            .flat_map(|pem| reqwest::Certificate::from_pem_bundle(&pem).unwrap_or_default())
+            .collect(),
+    };
+
+    match certificates.is_empty() {
+        true => Err(ClientInitError::NoRootCertificates(
+            "no CA certificates found; set SSL_CERT_FILE to a PEM bundle or add roots with \
+             ClientBuilder::root_certificate"
+                .to_string(),
+        )),
+        false => Ok(certificates),
+    }
+}
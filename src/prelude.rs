@@ -0,0 +1,33 @@
+//! Re-exports of the types most programs need.
+//!
+//! ```rust
+//! use cobalt_tools::prelude::*;
+//!
+//! let video_data = MediaRequestData {
+//!     url: "https://www.youtube.com/watch?v=1lML-Uem6Ns",
+//!     download_mode: Some(DownloadMode::Audio.to_string()),
+//!     ..Default::default()
+//! };
+//! ```
+
+pub use crate::api::{
+    builder::{AuthScheme, ClientBuilder, HttpVersion},
+    client::Client,
+    config::CobaltConfig,
+    options::{DownloadOptions, RequestOptions},
+    CobaltClient,
+};
+pub use crate::structs::{
+    download::{DownloadReport, DownloadStatus, Progress},
+    health::HealthReport,
+    media_error::MediaError,
+    media_request::{
+        DownloadMode, FilenameStyle, LangCode, MediaRequestData, MediaRequestPatch, VideoQuality,
+    },
+    media_response::{
+        ErrorResponse, MediaItem, MediaItemType, MediaKind, PickerResponse, RedirectResponse,
+        Response, Status,
+    },
+    services::Services,
+    StatusResponse,
+};
@@ -0,0 +1,208 @@
+use std::io::{self, Write};
+
+const LOCAL_HEADER_SIGNATURE: u32 = 0x0403_4b50;
+const DATA_DESCRIPTOR_SIGNATURE: u32 = 0x0807_4b50;
+const CENTRAL_HEADER_SIGNATURE: u32 = 0x0201_4b50;
+const END_OF_CENTRAL_DIRECTORY_SIGNATURE: u32 = 0x0605_4b50;
+
+/// Sizes and CRC follow the data in a descriptor; the name is UTF-8.
+const FLAGS: u16 = 0x0008 | 0x0800;
+/// Zip 2.0, the minimum version that understands data descriptors.
+const VERSION: u16 = 20;
+/// 1980-01-01 00:00, the earliest date a zip entry can carry.
+const DOS_DATE: u16 = 0x0021;
+
+/// Writes a zip archive entry by entry without knowing entry sizes up front.
+///
+/// Entries are stored uncompressed, since downloaded media is already compressed, and
+/// each one is followed by a data descriptor so its bytes can be streamed straight into
+/// the writer. Archives are limited to 4 GiB and 65535 entries (no zip64).
+///
+/// # Example
+/// ```rust
+/// use cobalt_tools::structs::archive::ZipWriter;
+///
+/// let mut zip = ZipWriter::new(Vec::new());
+/// zip.start_entry("hello.txt").unwrap();
+/// zip.write_data(b"Hello, world!").unwrap();
+/// let bytes = zip.finish().unwrap();
+/// assert_eq!(&bytes[..4], b"PK\x03\x04");
+/// ```
+pub struct ZipWriter<W: Write> {
+    writer: W,
+    offset: u64,
+    entries: Vec<ZipEntry>,
+    current: Option<ZipEntry>,
+}
+
+struct ZipEntry {
+    name: String,
+    offset: u32,
+    crc: u32,
+    size: u64,
+}
+
+impl<W: Write> ZipWriter<W> {
+    /// Creates a writer that writes the archive to `writer`.
+    pub fn new(writer: W) -> Self {
+        ZipWriter {
+            writer,
+            offset: 0,
+            entries: Vec::new(),
+            current: None,
+        }
+    }
+
+    /// Starts a new entry called `name`, finishing the previous one.
+    pub fn start_entry(&mut self, name: &str) -> io::Result<()> {
+        self.finish_entry()?;
+
+        if self.entries.len() >= u16::MAX as usize {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "zip archives hold at most 65535 entries",
+            ));
+        }
+
+        let offset = to_u32(self.offset)?;
+        let name_len = name_length(name)?;
+
+        let mut header = Vec::with_capacity(30 + name.len());
+        header.extend_from_slice(&LOCAL_HEADER_SIGNATURE.to_le_bytes());
+        header.extend_from_slice(&VERSION.to_le_bytes());
+        header.extend_from_slice(&FLAGS.to_le_bytes());
+        header.extend_from_slice(&0u16.to_le_bytes()); // stored
+        header.extend_from_slice(&0u16.to_le_bytes()); // time
+        header.extend_from_slice(&DOS_DATE.to_le_bytes());
+        header.extend_from_slice(&[0; 12]); // crc and sizes, in the descriptor
+        header.extend_from_slice(&name_len.to_le_bytes());
+        header.extend_from_slice(&0u16.to_le_bytes()); // extra field length
+        header.extend_from_slice(name.as_bytes());
+        self.write_raw(&header)?;
+
+        self.current = Some(ZipEntry {
+            name: name.to_string(),
+            offset,
+            crc: !0,
+            size: 0,
+        });
+        Ok(())
+    }
+
+    /// Appends `data` to the current entry.
+    pub fn write_data(&mut self, data: &[u8]) -> io::Result<()> {
+        let entry = self.current.as_mut().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "no zip entry was started")
+        })?;
+
+        entry.crc = crc32_update(entry.crc, data);
+        entry.size += data.len() as u64;
+        self.write_raw(data)
+    }
+
+    /// Finishes the current entry, if any, by writing its data descriptor.
+    pub fn finish_entry(&mut self) -> io::Result<()> {
+        let Some(mut entry) = self.current.take() else {
+            return Ok(());
+        };
+        entry.crc = !entry.crc;
+        let size = to_u32(entry.size)?;
+
+        let mut descriptor = Vec::with_capacity(16);
+        descriptor.extend_from_slice(&DATA_DESCRIPTOR_SIGNATURE.to_le_bytes());
+        descriptor.extend_from_slice(&entry.crc.to_le_bytes());
+        descriptor.extend_from_slice(&size.to_le_bytes());
+        descriptor.extend_from_slice(&size.to_le_bytes());
+        self.write_raw(&descriptor)?;
+
+        self.entries.push(entry);
+        Ok(())
+    }
+
+    /// Writes the central directory and returns the underlying writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.finish_entry()?;
+
+        let directory_offset = to_u32(self.offset)?;
+        let mut directory = Vec::new();
+        for entry in &self.entries {
+            let size = entry.size as u32;
+            directory.extend_from_slice(&CENTRAL_HEADER_SIGNATURE.to_le_bytes());
+            directory.extend_from_slice(&VERSION.to_le_bytes()); // made by
+            directory.extend_from_slice(&VERSION.to_le_bytes()); // needed
+            directory.extend_from_slice(&FLAGS.to_le_bytes());
+            directory.extend_from_slice(&0u16.to_le_bytes()); // stored
+            directory.extend_from_slice(&0u16.to_le_bytes()); // time
+            directory.extend_from_slice(&DOS_DATE.to_le_bytes());
+            directory.extend_from_slice(&entry.crc.to_le_bytes());
+            directory.extend_from_slice(&size.to_le_bytes());
+            directory.extend_from_slice(&size.to_le_bytes());
+            directory.extend_from_slice(&name_length(&entry.name)?.to_le_bytes());
+            directory.extend_from_slice(&[0; 12]); // extra, comment, disk, attributes
+            directory.extend_from_slice(&entry.offset.to_le_bytes());
+            directory.extend_from_slice(entry.name.as_bytes());
+        }
+        let directory_size = to_u32(directory.len() as u64)?;
+        let count = self.entries.len() as u16;
+
+        directory.extend_from_slice(&END_OF_CENTRAL_DIRECTORY_SIGNATURE.to_le_bytes());
+        directory.extend_from_slice(&[0; 4]); // disk numbers
+        directory.extend_from_slice(&count.to_le_bytes());
+        directory.extend_from_slice(&count.to_le_bytes());
+        directory.extend_from_slice(&directory_size.to_le_bytes());
+        directory.extend_from_slice(&directory_offset.to_le_bytes());
+        directory.extend_from_slice(&0u16.to_le_bytes()); // comment length
+        self.write_raw(&directory)?;
+
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+
+    fn write_raw(&mut self, data: &[u8]) -> io::Result<()> {
+        self.writer.write_all(data)?;
+        self.offset += data.len() as u64;
+        Ok(())
+    }
+}
+
+fn to_u32(value: u64) -> io::Result<u32> {
+    u32::try_from(value).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "zip archives without zip64 are limited to 4 GiB",
+        )
+    })
+}
+
+fn name_length(name: &str) -> io::Result<u16> {
+    u16::try_from(name.len())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "zip entry name is too long"))
+}
+
+/// Lookup table for CRC-32 (IEEE), built at compile time.
+const CRC32_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+/// Feeds `data` into a running CRC-32 (IEEE), starting from `!0`.
+pub(crate) fn crc32_update(crc: u32, data: &[u8]) -> u32 {
+    data.iter().fold(crc, |crc, &byte| {
+        CRC32_TABLE[((crc ^ byte as u32) & 0xFF) as usize] ^ (crc >> 8)
+    })
+}
@@ -0,0 +1,47 @@
+use std::fmt;
+
+use super::media_error::MediaError;
+
+/// A configuration problem that prevents a client from being created.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClientInitError {
+    /// A required environment variable is not set or not valid unicode.
+    MissingVar(&'static str),
+    /// The instance URI is not an absolute `http` or `https` URL.
+    InvalidUri { uri: String, reason: String },
+    /// The HTTP client could not be initialized.
+    HttpClient(String),
+    /// The `rustls` feature found no root certificates to trust, so every HTTPS request
+    /// would fail.
+    NoRootCertificates(String),
+}
+
+impl ClientInitError {
+    /// Converts an error of [`ClientBuilder::build`](crate::api::builder::ClientBuilder::build),
+    /// keeping configuration errors as they are.
+    pub(crate) fn from_build_error(error: MediaError) -> Self {
+        match error {
+            MediaError::ClientInit(error) => error,
+            error => ClientInitError::HttpClient(error.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for ClientInitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientInitError::MissingVar(name) => {
+                write!(f, "Missing Variable: expected {} in the environment", name)
+            }
+            ClientInitError::InvalidUri { uri, reason } => {
+                write!(f, "Invalid URI: {:?} ({})", uri, reason)
+            }
+            ClientInitError::HttpClient(msg) => write!(f, "HTTP Client Error: {}", msg),
+            ClientInitError::NoRootCertificates(reason) => {
+                write!(f, "No Root Certificates: {}", reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ClientInitError {}
@@ -0,0 +1,141 @@
+use std::{path::PathBuf, time::Duration};
+
+use super::media_response::{ErrorResponse, PickerResponse};
+
+/// The result of a conditional download.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DownloadStatus {
+    /// The file was downloaded.
+    Downloaded(DownloadReport),
+    /// The server reported the file unchanged since the given ETag; nothing was written.
+    NotModified,
+}
+
+/// What a finished download wrote, so callers don't need to inspect the file afterwards.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DownloadReport {
+    /// The file that was written.
+    pub path: PathBuf,
+    /// The size of the file once the download finished, including any bytes that were
+    /// already there when resuming.
+    pub bytes: u64,
+    /// How long the download took.
+    pub duration: Duration,
+    /// The `Content-Type` the server sent, if any.
+    pub content_type: Option<String>,
+    /// Whether an existing partial file was continued rather than downloaded from scratch.
+    pub resumed: bool,
+    /// The `ETag` the server sent, if any.
+    pub etag: Option<String>,
+}
+
+/// What [`Client::download_media`](crate::api::client::Client::download_media) did with
+/// the instance's response.
+#[derive(Debug)]
+pub enum DownloadOutcome {
+    /// The response was a tunnel or redirect, and the file was downloaded.
+    Downloaded(DownloadReport),
+    /// The response was a picker; nothing was downloaded, so the caller can choose an
+    /// item.
+    Picker(PickerResponse),
+    /// The instance answered with an error.
+    Failed(ErrorResponse),
+}
+
+/// A snapshot of a download in progress, passed to progress callbacks.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Progress {
+    /// Bytes of the file on disk so far, including those kept from an earlier,
+    /// resumed download.
+    pub downloaded: u64,
+    /// The full size of the file, when the server sent a `Content-Length` or, when
+    /// resuming, a `Content-Range`.
+    pub total: Option<u64>,
+    /// The smoothed transfer speed in bytes per second.
+    pub speed: f64,
+    /// Bytes already on disk when the download was resumed, or 0.
+    pub resumed_from: u64,
+    /// The estimated time left at the smoothed `speed`, or `None` while the size or
+    /// the speed is unknown.
+    pub eta: Option<Duration>,
+}
+
+/// Estimates the time left to download `total` bytes at `speed` bytes per second.
+///
+/// Returns `None` when the estimate does not fit in a [`Duration`].
+pub(crate) fn eta(downloaded: u64, total: Option<u64>, speed: f64) -> Option<Duration> {
+    if speed <= 0.0 {
+        return None;
+    }
+    let remaining = total?.saturating_sub(downloaded);
+    Duration::try_from_secs_f64(remaining as f64 / speed).ok()
+}
+
+/// An update sent by [`Client::download_with_channel`](crate::api::client::Client::download_with_channel).
+#[derive(Debug, Clone, PartialEq)]
+pub enum DownloadEvent {
+    /// More bytes were written.
+    Progress(Progress),
+    /// The download finished; always the last event on success.
+    Finished(DownloadStatus),
+    /// The download failed with the given error message; always the last event on failure.
+    Failed(String),
+}
+
+/// A single download in a batch started with
+/// [`Client::download_batch`](crate::api::client::Client::download_batch).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DownloadJob {
+    /// The tunnel or redirect URL to download.
+    pub url: String,
+    /// Where to write the file.
+    pub path: PathBuf,
+}
+
+impl DownloadJob {
+    pub fn new(url: impl Into<String>, path: impl Into<PathBuf>) -> Self {
+        DownloadJob {
+            url: url.into(),
+            path: path.into(),
+        }
+    }
+}
+
+/// Progress of a whole batch, summed over its jobs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BatchProgress {
+    /// Jobs that have finished or failed.
+    pub finished: usize,
+    /// Jobs in the batch.
+    pub jobs: usize,
+    /// Bytes written so far across all jobs.
+    pub downloaded: u64,
+    /// The summed sizes of the jobs that reported a `Content-Length` so far. It grows as
+    /// more jobs start.
+    pub total: u64,
+}
+
+/// An update from a batch download. `job` is the index of the job it refers to.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BatchEvent {
+    /// The job's download started.
+    Started { job: usize },
+    /// The job wrote more bytes.
+    Progress {
+        job: usize,
+        progress: Progress,
+        overall: BatchProgress,
+    },
+    /// The job finished.
+    Finished {
+        job: usize,
+        status: DownloadStatus,
+        overall: BatchProgress,
+    },
+    /// The job failed with the given error message.
+    Failed {
+        job: usize,
+        error: String,
+        overall: BatchProgress,
+    },
+}
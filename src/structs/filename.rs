@@ -0,0 +1,113 @@
+use std::path::Path;
+
+use super::{media_request::MediaRequestData, media_response::Response, service};
+
+/// Characters that are not allowed in file names on at least one common platform.
+const RESERVED_CHARACTERS: &[char] = &['/', '\\', ':', '*', '?', '"', '<', '>', '|'];
+
+/// Makes `name` safe to use as a single path component.
+///
+/// Path separators, characters reserved on Windows and control characters are replaced
+/// with `_`, and trailing dots and spaces are trimmed. A name that ends up empty, `.` or
+/// `..` becomes `_`.
+pub fn sanitize_filename(name: &str) -> String {
+    let sanitized: String = name
+        .chars()
+        .map(|c| {
+            if RESERVED_CHARACTERS.contains(&c) || c.is_control() {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect();
+    let sanitized = sanitized.trim_end_matches(['.', ' ']);
+
+    if sanitized.is_empty() {
+        "_".to_string()
+    } else {
+        sanitized.to_string()
+    }
+}
+
+/// Builds a file name from `template`, substituting fields of the request and response.
+///
+/// Supported placeholders:
+/// - `{service}`: the service of the request URL, e.g. `youtube`
+/// - `{filename}`: the file name suggested by the instance, without its extension
+/// - `{ext}`: the extension of the suggested file name
+/// - `{quality}`: the requested video quality
+/// - `{codec}`: the requested YouTube video codec
+/// - `{audio_format}`: the requested audio format
+/// - `{mode}`: the requested download mode
+///
+/// Placeholders whose value isn't available, as well as unknown placeholders, are replaced
+/// with an empty string. Substituted values are passed through [`sanitize_filename`], so
+/// they cannot introduce path separators; text written in the template itself is kept
+/// as is.
+///
+/// # Example
+/// ```rust
+/// use cobalt_tools::structs::{
+///     filename::format_filename,
+///     media_request::MediaRequestData,
+///     media_response::{RedirectResponse, Response},
+/// };
+///
+/// let request = MediaRequestData {
+///     url: "https://www.youtube.com/watch?v=1lML-Uem6Ns",
+///     video_quality: Some("1080"),
+///     ..Default::default()
+/// };
+/// let response = Response::Redirect(RedirectResponse {
+///     status: "redirect".to_string(),
+///     url: "https://example.com/video.mp4".to_string(),
+///     filename: "1lML-Uem6Ns.mp4".to_string(),
+/// });
+///
+/// let name = format_filename("{service}-{filename}-{quality}.{ext}", &request, &response);
+/// assert_eq!(name, "youtube-1lML-Uem6Ns-1080.mp4");
+/// ```
+pub fn format_filename(template: &str, request: &MediaRequestData, response: &Response) -> String {
+    let suggested = match response {
+        Response::Redirect(redirect) => Some(redirect.filename.as_str()),
+        Response::Picker(picker) => picker.audio_filename.as_deref(),
+        Response::Error(_) => None,
+    }
+    .map(Path::new);
+
+    let mut formatted = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        let Some(end) = rest[start..].find('}').map(|end| start + end) else {
+            break;
+        };
+
+        formatted.push_str(&rest[..start]);
+
+        let value = match &rest[start + 1..end] {
+            "service" => service::service_for_url(request.url),
+            "filename" => suggested
+                .and_then(Path::file_stem)
+                .and_then(|stem| stem.to_str()),
+            "ext" => suggested
+                .and_then(Path::extension)
+                .and_then(|ext| ext.to_str()),
+            "quality" => request.video_quality,
+            "codec" => request.youtube_video_codec,
+            "audio_format" => request.audio_format,
+            "mode" => request.download_mode,
+            _ => None,
+        };
+
+        if let Some(value) = value {
+            formatted.push_str(&sanitize_filename(value));
+        }
+
+        rest = &rest[end + 1..];
+    }
+
+    formatted.push_str(rest);
+    formatted
+}
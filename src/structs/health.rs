@@ -0,0 +1,37 @@
+use std::time::Duration;
+
+/// The result of one check of a [`HealthReport`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CheckOutcome {
+    Passed,
+    /// The check failed, with the reason.
+    Failed(String),
+    /// The check wasn't run because an earlier one failed.
+    Skipped,
+}
+
+impl CheckOutcome {
+    pub fn is_passed(&self) -> bool {
+        *self == CheckOutcome::Passed
+    }
+}
+
+/// The result of [`Client::healthcheck`](crate::api::client::Client::healthcheck).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HealthReport {
+    /// How long the status request took, whether or not it succeeded.
+    pub status_latency: Duration,
+    /// Whether the instance URI answered with a cobalt status.
+    pub instance: CheckOutcome,
+    /// The cobalt version the instance reported, if it answered.
+    pub version: Option<String>,
+    /// Whether the instance accepted the API key for a media request.
+    pub auth: CheckOutcome,
+}
+
+impl HealthReport {
+    /// Returns `true` if every check passed.
+    pub fn is_healthy(&self) -> bool {
+        self.instance.is_passed() && self.auth.is_passed()
+    }
+}
@@ -1,23 +1,110 @@
-use std::fmt;
+use reqwest::StatusCode;
+use std::{fmt, time::Duration};
 
-use serde::{Deserialize, Serialize};
-
-#[derive(Debug, Deserialize, Serialize)]
-struct ApiErrorResponse {
-    status: String,
-    error: ApiErrorDetails,
-}
-
-#[derive(Debug, Deserialize, Serialize)]
-struct ApiErrorDetails {
-    code: String,
-}
+use super::{client_init_error::ClientInitError, media_response::ErrorResponse};
 
 #[derive(Debug)]
 pub enum MediaError {
     RequestError(String),
     DeserializationError(String),
+    /// No longer returned; HTTP errors are reported as `MediaError::Api`.
+    #[deprecated(note = "use MediaError::Api")]
     ApiError(String),
+    /// The instance or tunnel answered with a non-success HTTP status.
+    Api {
+        status: StatusCode,
+        body: String,
+    },
+    /// Cobalt reported an error in the body of a successful response.
+    Cobalt(ErrorResponse),
+    InvalidInstance(String),
+    IoError(String),
+    InvalidRequest(String),
+    EmptyResponse,
+    UnexpectedContentType(String),
+    IndexOutOfRange {
+        index: usize,
+        len: usize,
+    },
+    DeadlineExceeded,
+    /// The media is longer than the instance's duration limit.
+    TooLong {
+        duration: Duration,
+        limit: Duration,
+    },
+    /// The server ignored a `Range` request and would have sent the whole file.
+    RangeNotSupported,
+    /// Resolving media kept yielding links to other media pages instead of a file.
+    TooManyRedirects {
+        limit: u32,
+    },
+    /// A download received no data for longer than its idle timeout.
+    StalledDownload {
+        idle: Duration,
+    },
+    /// A download would have overwritten an existing file.
+    FileExists(String),
+    /// The client could not be set up.
+    ClientInit(ClientInitError),
+    /// The operation was stopped by [`Client::shutdown`](crate::api::client::Client::shutdown).
+    Cancelled,
+}
+
+impl MediaError {
+    /// Returns `true` for failures that may go away when the operation is repeated.
+    pub(crate) fn is_retryable(&self) -> bool {
+        match self {
+            MediaError::RequestError(_)
+            | MediaError::EmptyResponse
+            | MediaError::StalledDownload { .. } => true,
+            MediaError::Api { status, .. } => {
+                status.is_server_error() || *status == StatusCode::TOO_MANY_REQUESTS
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns the error response cobalt reported, whether it came in the body of a
+    /// successful response (`MediaError::Cobalt`) or with an error status
+    /// (`MediaError::Api` with an error response as the body).
+    ///
+    /// Returns `None` for every other error, including `Api` errors whose body isn't an
+    /// error response, e.g. from a reverse proxy.
+    pub fn error_response(&self) -> Option<ErrorResponse> {
+        match self {
+            MediaError::Cobalt(error) => Some(error.clone()),
+            MediaError::Api { body, .. } => serde_json::from_str(body).ok(),
+            _ => None,
+        }
+    }
+}
+
+impl MediaError {
+    /// Builds a `MediaError::Api` from a non-success response, reading its body as text.
+    ///
+    /// The body is trimmed, and if it can't be read, the reason takes its place so the
+    /// status code is never lost.
+    pub(crate) async fn from_response(response: reqwest::Response) -> MediaError {
+        let status = response.status();
+        let body = response.text().await;
+        MediaError::api(status, body)
+    }
+
+    /// Like [`MediaError::from_response`], for a blocking response.
+    #[cfg(feature = "blocking")]
+    pub(crate) fn from_blocking_response(response: reqwest::blocking::Response) -> MediaError {
+        let status = response.status();
+        let body = response.text();
+        MediaError::api(status, body)
+    }
+
+    fn api(status: StatusCode, body: Result<String, reqwest::Error>) -> MediaError {
+        let body = match body {
+            Ok(body) => body.trim().to_string(),
+            Err(err) => format!("<failed to read body: {}>", err),
+        };
+        MediaError::Api { status, body }
+    }
 }
 
 impl fmt::Display for MediaError {
@@ -25,7 +112,81 @@ impl fmt::Display for MediaError {
         match self {
             MediaError::RequestError(msg) => write!(f, "Request Error: {}", msg),
             MediaError::DeserializationError(msg) => write!(f, "Deserialization Error: {}", msg),
+            #[allow(deprecated)]
             MediaError::ApiError(msg) => write!(f, "API Error: {}", msg),
+            MediaError::Api { status, body } if body.is_empty() => {
+                write!(f, "API Error: HTTP {}", status)
+            }
+            MediaError::Api { status, body } => write!(f, "API Error: HTTP {} | {}", status, body),
+            MediaError::Cobalt(error) => write!(f, "Cobalt Error: {}", error.error.code),
+            MediaError::InvalidInstance(msg) => write!(f, "Invalid Instance: {}", msg),
+            MediaError::IoError(msg) => write!(f, "IO Error: {}", msg),
+            MediaError::InvalidRequest(msg) => write!(f, "Invalid Request: {}", msg),
+            MediaError::EmptyResponse => write!(f, "Empty Response: the instance returned no body"),
+            MediaError::UnexpectedContentType(msg) => {
+                write!(f, "Unexpected Content Type: {}", msg)
+            }
+            MediaError::IndexOutOfRange { index, len } => {
+                write!(
+                    f,
+                    "Index Out Of Range: item {} requested, but there are {}",
+                    index, len
+                )
+            }
+            MediaError::DeadlineExceeded => write!(f, "Deadline Exceeded"),
+            MediaError::TooLong { duration, limit } => write!(
+                f,
+                "Too Long: media is {}s, the instance allows {}s",
+                duration.as_secs(),
+                limit.as_secs()
+            ),
+            MediaError::RangeNotSupported => write!(
+                f,
+                "Range Not Supported: the server does not serve partial content"
+            ),
+            MediaError::TooManyRedirects { limit } => write!(
+                f,
+                "Too Many Redirects: media still unresolved after {} re-resolutions",
+                limit
+            ),
+            MediaError::StalledDownload { idle } => write!(
+                f,
+                "Stalled Download: no data received for {}s",
+                idle.as_secs_f64()
+            ),
+            MediaError::FileExists(path) => write!(f, "File Exists: {}", path),
+            MediaError::ClientInit(error) => write!(f, "Client Init Error: {}", error),
+            MediaError::Cancelled => write!(f, "Cancelled: the client was shut down"),
         }
     }
-}
\ No newline at end of file
+}
+
+impl std::error::Error for MediaError {}
+
+impl From<reqwest::Error> for MediaError {
+    fn from(error: reqwest::Error) -> Self {
+        if error.is_decode() {
+            MediaError::DeserializationError(error.to_string())
+        } else {
+            MediaError::RequestError(error.to_string())
+        }
+    }
+}
+
+impl From<ClientInitError> for MediaError {
+    fn from(error: ClientInitError) -> Self {
+        MediaError::ClientInit(error)
+    }
+}
+
+impl From<serde_json::Error> for MediaError {
+    fn from(error: serde_json::Error) -> Self {
+        MediaError::DeserializationError(error.to_string())
+    }
+}
+
+impl From<std::io::Error> for MediaError {
+    fn from(error: std::io::Error) -> Self {
+        MediaError::IoError(error.to_string())
+    }
+}
@@ -1,6 +1,10 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize, Default, Debug)]
+use crate::api::builder::{DEFAULT_ACCEPT, DEFAULT_CONTENT_TYPE, DEFAULT_USER_AGENT};
+
+use super::{media_error::MediaError, service};
+
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
 pub struct MediaRequestData<'a> {
     pub url: &'a str,
     #[serde(rename = "videoQuality", skip_serializing_if = "Option::is_none")]
@@ -9,8 +13,10 @@ pub struct MediaRequestData<'a> {
     pub audio_format: Option<&'a str>,
     #[serde(rename = "audioBitrate", skip_serializing_if = "Option::is_none")]
     pub audio_bitrate: Option<&'a str>,
-    #[serde(rename = "filenameStyle")]
-    pub filename_style: &'a str,
+    /// Left out of the request body while `None`, so the instance uses its default
+    /// style. See [`FilenameStyle`] for the accepted values.
+    #[serde(rename = "filenameStyle", skip_serializing_if = "Option::is_none")]
+    pub filename_style: Option<&'a str>,
     #[serde(rename = "downloadMode", skip_serializing_if = "Option::is_none")]
     pub download_mode: Option<&'a str>,
     #[serde(rename = "youtubeVideoCodec", skip_serializing_if = "Option::is_none")]
@@ -25,12 +31,469 @@ pub struct MediaRequestData<'a> {
     pub tiktok_full_audio: Option<bool>,
     #[serde(rename = "tiktokH265", skip_serializing_if = "Option::is_none")]
     pub tiktok_h265: Option<bool>,
+    /// Converts Twitter/X GIFs, which are served as videos, back to `.gif`. Only applies to
+    /// twitter/x URLs.
+    #[serde(rename = "twitterGif", skip_serializing_if = "Option::is_none")]
+    pub twitter_gif: Option<bool>,
+    /// Converts looping GIF videos to `.gif`, for instances that use the newer,
+    /// service-independent name of `twitterGif`.
+    #[serde(rename = "convertGif", skip_serializing_if = "Option::is_none")]
+    pub convert_gif: Option<bool>,
+    #[serde(rename = "youtubeHLS", skip_serializing_if = "Option::is_none")]
+    pub youtube_hls: Option<bool>,
+}
+
+impl<'a> MediaRequestData<'a> {
+    /// Returns a copy of the request with every field set in `overrides` replaced.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cobalt_tools::structs::media_request::{MediaRequestData, MediaRequestPatch};
+    ///
+    /// let defaults = MediaRequestData {
+    ///     video_quality: Some("1080"),
+    ///     youtube_video_codec: Some("vp9"),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let video_data = defaults.merge(&MediaRequestPatch {
+    ///     url: Some("https://www.youtube.com/watch?v=1lML-Uem6Ns"),
+    ///     video_quality: Some("720"),
+    ///     ..Default::default()
+    /// });
+    ///
+    /// assert_eq!(video_data.video_quality, Some("720"));
+    /// assert_eq!(video_data.youtube_video_codec, Some("vp9"));
+    /// ```
+    pub fn merge(&self, overrides: &MediaRequestPatch<'a>) -> MediaRequestData<'a> {
+        MediaRequestData {
+            url: overrides.url.unwrap_or(self.url),
+            video_quality: overrides.video_quality.or(self.video_quality),
+            audio_format: overrides.audio_format.or(self.audio_format),
+            audio_bitrate: overrides.audio_bitrate.or(self.audio_bitrate),
+            filename_style: overrides.filename_style.or(self.filename_style),
+            download_mode: overrides.download_mode.or(self.download_mode),
+            youtube_video_codec: overrides.youtube_video_codec.or(self.youtube_video_codec),
+            youtube_dub_lang: overrides.youtube_dub_lang.or(self.youtube_dub_lang),
+            always_proxy: overrides.always_proxy.or(self.always_proxy),
+            disable_metadata: overrides.disable_metadata.or(self.disable_metadata),
+            tiktok_full_audio: overrides.tiktok_full_audio.or(self.tiktok_full_audio),
+            tiktok_h265: overrides.tiktok_h265.or(self.tiktok_h265),
+            twitter_gif: overrides.twitter_gif.or(self.twitter_gif),
+            convert_gif: overrides.convert_gif.or(self.convert_gif),
+            youtube_hls: overrides.youtube_hls.or(self.youtube_hls),
+        }
+    }
+}
+
+impl MediaRequestData<'_> {
+    /// Checks the request for options that don't apply to its URL.
+    ///
+    /// `get_media` runs this before sending anything.
+    ///
+    /// # Errors
+    /// Returns `MediaError::InvalidRequest` if `twitter_gif` is enabled for a URL that
+    /// isn't a twitter/x URL, or if `youtube_dub_lang` is not a plausible language code.
+    pub fn validate(&self) -> Result<(), MediaError> {
+        if let Some(lang) = self.youtube_dub_lang {
+            LangCode::new(lang)?;
+        }
+
+        if self.twitter_gif == Some(true) && service::service_for_url(self.url) != Some("twitter") {
+            return Err(MediaError::InvalidRequest(format!(
+                "twitter_gif only applies to twitter/x URLs, got {}",
+                self.log_summary()
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Returns a short, log-safe description of the request.
+    ///
+    /// Only the host of the source URL is included, never its path, query string or
+    /// credentials, followed by the selected options, e.g. `youtube.com · 1080p · h264`.
+    pub fn log_summary(&self) -> String {
+        let host = reqwest::Url::parse(self.url)
+            .ok()
+            .and_then(|url| {
+                url.host_str()
+                    .map(|host| host.trim_start_matches("www.").to_string())
+            })
+            .unwrap_or_else(|| "<invalid url>".to_string());
+
+        let quality = self.video_quality.map(|quality| match quality {
+            "max" => quality.to_string(),
+            _ => format!("{}p", quality),
+        });
+        let bitrate = self.audio_bitrate.map(|bitrate| format!("{}kbps", bitrate));
+
+        let options = [
+            self.download_mode.map(str::to_string),
+            quality,
+            self.youtube_video_codec.map(str::to_string),
+            self.audio_format.map(str::to_string),
+            bitrate,
+            self.youtube_dub_lang.map(str::to_string),
+        ];
+
+        std::iter::once(host)
+            .chain(options.into_iter().flatten())
+            .collect::<Vec<_>>()
+            .join(" · ")
+    }
+
+    /// Returns an owned copy of the request that can be stored and restored.
+    pub fn to_owned_snapshot(&self) -> MediaRequestSnapshot {
+        MediaRequestSnapshot::from(self)
+    }
+
+    /// Loads an owned request from JSON in the format cobalt accepts, e.g. a preset kept
+    /// in a config file.
+    ///
+    /// `url` may be left out, so presets can be stored without one and the URL set before
+    /// sending.
+    ///
+    /// # Errors
+    /// Returns `MediaError::DeserializationError` if `json` isn't a valid request.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cobalt_tools::structs::media_request::MediaRequestData;
+    ///
+    /// let mut podcast = MediaRequestData::from_json(
+    ///     r#"{ "downloadMode": "audio", "audioFormat": "mp3", "audioBitrate": "128" }"#,
+    /// )
+    /// .expect("Invalid preset");
+    /// podcast.url = "https://soundcloud.com/user/episode".to_string();
+    ///
+    /// assert_eq!(podcast.as_request().audio_format, Some("mp3"));
+    /// ```
+    pub fn from_json(json: &str) -> Result<MediaRequestSnapshot, MediaError> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Returns a stable key identifying the request, e.g. for caching resolved responses.
+    ///
+    /// The key is a 64-bit FNV-1a hash of the request body, as 16 hex digits. Unset
+    /// options are left out of the body, and the URL is trimmed, so requests that send
+    /// the same body get the same key across runs and crate builds.
+    pub fn cache_key(&self) -> String {
+        let normalized = MediaRequestData {
+            url: self.url.trim(),
+            ..self.clone()
+        };
+        let body = serde_json::to_vec(&normalized).unwrap_or_default();
+
+        format!("{:016x}", fnv1a(&body))
+    }
+
+    /// Returns a `curl` command that sends this request to `instance_uri` with the
+    /// client's default headers, for reproducing a request outside the crate.
+    ///
+    /// The API key is replaced by `api_key_placeholder`, so the command can be shared
+    /// in bug reports as-is.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cobalt_tools::structs::media_request::MediaRequestData;
+    ///
+    /// let video_data = MediaRequestData {
+    ///     url: "https://www.youtube.com/watch?v=1lML-Uem6Ns",
+    ///     ..Default::default()
+    /// };
+    ///
+    /// println!("{}", video_data.to_curl("http://localhost:9000", "<api-key>"));
+    /// ```
+    pub fn to_curl(&self, instance_uri: &str, api_key_placeholder: &str) -> String {
+        let body = serde_json::to_string(self).unwrap_or_default();
+        let headers = [
+            format!("Content-Type: {}", DEFAULT_CONTENT_TYPE),
+            format!("Accept: {}", DEFAULT_ACCEPT),
+            format!("User-Agent: {}", DEFAULT_USER_AGENT),
+            format!("Authorization: Api-Key {}", api_key_placeholder),
+        ];
+
+        let mut command = format!("curl -X POST {}", shell_quote(instance_uri));
+        for header in headers {
+            command.push_str(&format!(" \\\n  -H {}", shell_quote(&header)));
+        }
+        command.push_str(&format!(" \\\n  -d {}", shell_quote(&body)));
+        command
+    }
+}
+
+/// Fields to override in a [`MediaRequestData`] with [`MediaRequestData::merge`].
+///
+/// Fields left as `None` keep the value of the request being merged into.
+#[derive(Debug, Default, Clone)]
+pub struct MediaRequestPatch<'a> {
+    pub url: Option<&'a str>,
+    pub video_quality: Option<&'a str>,
+    pub audio_format: Option<&'a str>,
+    pub audio_bitrate: Option<&'a str>,
+    pub filename_style: Option<&'a str>,
+    pub download_mode: Option<&'a str>,
+    pub youtube_video_codec: Option<&'a str>,
+    pub youtube_dub_lang: Option<&'a str>,
+    pub always_proxy: Option<bool>,
+    pub disable_metadata: Option<bool>,
+    pub tiktok_full_audio: Option<bool>,
+    pub tiktok_h265: Option<bool>,
+    pub twitter_gif: Option<bool>,
+    pub convert_gif: Option<bool>,
+    pub youtube_hls: Option<bool>,
+}
+
+/// An owned copy of a [`MediaRequestData`], for storing requests beyond the lifetime of
+/// the strings they borrow, e.g. in a download queue on disk.
+///
+/// It serializes to the same JSON as the request it was made from.
+///
+/// # Example
+/// ```rust
+/// use cobalt_tools::structs::media_request::{MediaRequestData, MediaRequestSnapshot};
+///
+/// let video_data = MediaRequestData {
+///     url: "https://www.youtube.com/watch?v=1lML-Uem6Ns",
+///     ..Default::default()
+/// };
+///
+/// let saved = serde_json::to_string(&video_data.to_owned_snapshot()).unwrap();
+/// let restored: MediaRequestSnapshot = serde_json::from_str(&saved).unwrap();
+/// assert_eq!(restored.as_request().url, video_data.url);
+/// ```
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq, Eq)]
+pub struct MediaRequestSnapshot {
+    #[serde(default)]
+    pub url: String,
+    #[serde(rename = "videoQuality", skip_serializing_if = "Option::is_none")]
+    pub video_quality: Option<String>,
+    #[serde(rename = "audioFormat", skip_serializing_if = "Option::is_none")]
+    pub audio_format: Option<String>,
+    #[serde(rename = "audioBitrate", skip_serializing_if = "Option::is_none")]
+    pub audio_bitrate: Option<String>,
+    #[serde(rename = "filenameStyle", skip_serializing_if = "Option::is_none")]
+    pub filename_style: Option<String>,
+    #[serde(rename = "downloadMode", skip_serializing_if = "Option::is_none")]
+    pub download_mode: Option<String>,
+    #[serde(rename = "youtubeVideoCodec", skip_serializing_if = "Option::is_none")]
+    pub youtube_video_codec: Option<String>,
+    #[serde(rename = "youtubeDubLang", skip_serializing_if = "Option::is_none")]
+    pub youtube_dub_lang: Option<String>,
+    #[serde(rename = "alwaysProxy", skip_serializing_if = "Option::is_none")]
+    pub always_proxy: Option<bool>,
+    #[serde(rename = "disableMetadata", skip_serializing_if = "Option::is_none")]
+    pub disable_metadata: Option<bool>,
+    #[serde(rename = "tiktokFullAudio", skip_serializing_if = "Option::is_none")]
+    pub tiktok_full_audio: Option<bool>,
+    #[serde(rename = "tiktokH265", skip_serializing_if = "Option::is_none")]
+    pub tiktok_h265: Option<bool>,
     #[serde(rename = "twitterGif", skip_serializing_if = "Option::is_none")]
     pub twitter_gif: Option<bool>,
+    #[serde(rename = "convertGif", skip_serializing_if = "Option::is_none")]
+    pub convert_gif: Option<bool>,
     #[serde(rename = "youtubeHLS", skip_serializing_if = "Option::is_none")]
     pub youtube_hls: Option<bool>,
 }
 
+impl MediaRequestSnapshot {
+    /// Borrows the snapshot as a request that can be passed to `get_media`.
+    pub fn as_request(&self) -> MediaRequestData<'_> {
+        MediaRequestData {
+            url: &self.url,
+            video_quality: self.video_quality.as_deref(),
+            audio_format: self.audio_format.as_deref(),
+            audio_bitrate: self.audio_bitrate.as_deref(),
+            filename_style: self.filename_style.as_deref(),
+            download_mode: self.download_mode.as_deref(),
+            youtube_video_codec: self.youtube_video_codec.as_deref(),
+            youtube_dub_lang: self.youtube_dub_lang.as_deref(),
+            always_proxy: self.always_proxy,
+            disable_metadata: self.disable_metadata,
+            tiktok_full_audio: self.tiktok_full_audio,
+            tiktok_h265: self.tiktok_h265,
+            twitter_gif: self.twitter_gif,
+            convert_gif: self.convert_gif,
+            youtube_hls: self.youtube_hls,
+        }
+    }
+}
+
+impl From<&MediaRequestData<'_>> for MediaRequestSnapshot {
+    fn from(data: &MediaRequestData<'_>) -> Self {
+        MediaRequestSnapshot {
+            url: data.url.to_string(),
+            video_quality: data.video_quality.map(str::to_string),
+            audio_format: data.audio_format.map(str::to_string),
+            audio_bitrate: data.audio_bitrate.map(str::to_string),
+            filename_style: data.filename_style.map(str::to_string),
+            download_mode: data.download_mode.map(str::to_string),
+            youtube_video_codec: data.youtube_video_codec.map(str::to_string),
+            youtube_dub_lang: data.youtube_dub_lang.map(str::to_string),
+            always_proxy: data.always_proxy,
+            disable_metadata: data.disable_metadata,
+            tiktok_full_audio: data.tiktok_full_audio,
+            tiktok_h265: data.tiktok_h265,
+            twitter_gif: data.twitter_gif,
+            convert_gif: data.convert_gif,
+            youtube_hls: data.youtube_hls,
+        }
+    }
+}
+
+impl From<MediaRequestData<'_>> for MediaRequestSnapshot {
+    fn from(data: MediaRequestData<'_>) -> Self {
+        MediaRequestSnapshot::from(&data)
+    }
+}
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// Hashes `bytes` with 64-bit FNV-1a, which, unlike std's hashers, is stable across runs
+/// and Rust versions.
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(FNV_OFFSET_BASIS, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(FNV_PRIME)
+    })
+}
+
+/// Wraps `value` in single quotes for a POSIX shell.
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+/// A language code for `youtube_dub_lang`, such as `en`, `pt-BR` or `zh-Hans`.
+///
+/// Cobalt silently ignores dub languages it doesn't understand, so the code is checked to
+/// be a plausible ISO 639 / BCP 47 tag: a 2–3 letter language subtag followed by optional
+/// alphanumeric subtags of up to 8 characters.
+///
+/// # Example
+/// ```rust
+/// use cobalt_tools::structs::media_request::{LangCode, MediaRequestData};
+///
+/// let lang = LangCode::new("pt-BR").expect("Invalid language code");
+///
+/// let video_data = MediaRequestData {
+///     url: "https://www.youtube.com/watch?v=1lML-Uem6Ns",
+///     youtube_dub_lang: Some(lang.as_str()),
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LangCode<'a>(&'a str);
+
+impl<'a> LangCode<'a> {
+    /// Validates `code` as a language code.
+    ///
+    /// # Errors
+    /// Returns `MediaError::InvalidRequest` if `code` is not a plausible language code.
+    pub fn new(code: &'a str) -> Result<Self, MediaError> {
+        let mut subtags = code.split('-');
+        let language = subtags.next().unwrap_or_default();
+
+        let valid = (2..=3).contains(&language.len())
+            && language.chars().all(|c| c.is_ascii_alphabetic())
+            && subtags.all(|subtag| {
+                (1..=8).contains(&subtag.len()) && subtag.chars().all(|c| c.is_ascii_alphanumeric())
+            });
+
+        if !valid {
+            return Err(MediaError::InvalidRequest(format!(
+                "{:?} is not a valid language code",
+                code
+            )));
+        }
+
+        Ok(LangCode(code))
+    }
+
+    /// Returns the language code as passed to [`LangCode::new`].
+    pub fn as_str(&self) -> &'a str {
+        self.0
+    }
+}
+
+/// A value for `video_quality`: the target height in pixels, or `Max` for the best
+/// available.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VideoQuality {
+    Max,
+    P4320,
+    P2160,
+    P1440,
+    P1080,
+    P720,
+    P480,
+    P360,
+    P240,
+    P144,
+}
+
+impl VideoQuality {
+    /// Every quality cobalt accepts, best first.
+    pub const ALL: &'static [VideoQuality] = &[
+        VideoQuality::Max,
+        VideoQuality::P4320,
+        VideoQuality::P2160,
+        VideoQuality::P1440,
+        VideoQuality::P1080,
+        VideoQuality::P720,
+        VideoQuality::P480,
+        VideoQuality::P360,
+        VideoQuality::P240,
+        VideoQuality::P144,
+    ];
+
+    /// Returns the value cobalt expects, e.g. `"1080"` or `"max"`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            VideoQuality::Max => "max",
+            VideoQuality::P4320 => "4320",
+            VideoQuality::P2160 => "2160",
+            VideoQuality::P1440 => "1440",
+            VideoQuality::P1080 => "1080",
+            VideoQuality::P720 => "720",
+            VideoQuality::P480 => "480",
+            VideoQuality::P360 => "360",
+            VideoQuality::P240 => "240",
+            VideoQuality::P144 => "144",
+        }
+    }
+}
+
+/// A value for `filename_style`. `Basic` is cobalt's default.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum FilenameStyle {
+    Classic,
+    #[default]
+    Basic,
+    Pretty,
+    Nerdy,
+}
+
+impl FilenameStyle {
+    /// Every filename style cobalt accepts.
+    pub const ALL: &'static [FilenameStyle] = &[
+        FilenameStyle::Classic,
+        FilenameStyle::Basic,
+        FilenameStyle::Pretty,
+        FilenameStyle::Nerdy,
+    ];
+
+    /// Returns the value cobalt expects, e.g. `"basic"`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FilenameStyle::Classic => "classic",
+            FilenameStyle::Basic => "basic",
+            FilenameStyle::Pretty => "pretty",
+            FilenameStyle::Nerdy => "nerdy",
+        }
+    }
+}
+
 #[derive(Debug, PartialEq)]
 pub enum DownloadMode {
     Auto,
@@ -39,6 +502,10 @@ pub enum DownloadMode {
 }
 
 impl DownloadMode {
+    /// Every download mode cobalt accepts.
+    pub const ALL: &'static [DownloadMode] =
+        &[DownloadMode::Auto, DownloadMode::Audio, DownloadMode::Mute];
+
     pub fn to_string(&self) -> &str {
         match self {
             DownloadMode::Auto => "auto",
@@ -47,6 +514,7 @@ impl DownloadMode {
         }
     }
 
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(input: &str) -> Option<DownloadMode> {
         match input {
             "auto" => Some(DownloadMode::Auto),
@@ -1,4 +1,11 @@
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use super::{filename::sanitize_filename, media_error::MediaError};
 
 #[derive(Debug)]
 pub enum Status {
@@ -7,24 +14,85 @@ pub enum Status {
     Redirect
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ErrorContext {
     pub service: Option<String>,
     pub limit: Option<u64>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ErrorDetails {
     pub code: String,
     pub context: Option<ErrorContext>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ErrorResponse {
     pub status: String,
     pub error: ErrorDetails,
 }
 
+impl ErrorResponse {
+    /// Returns the instance's rate-limit window when cobalt rejected the request for
+    /// exceeding its rate limit.
+    ///
+    /// Cobalt reports this as `error.api.rate_exceeded`, with the window in seconds as
+    /// the context's `limit`; it is not a request count. `error.api.rate_limit` is
+    /// accepted as well. Any other error code yields `None`, even if it carries a limit.
+    pub fn rate_limit(&self) -> Option<Duration> {
+        match self.error.code.as_str() {
+            "error.api.rate_exceeded" | "error.api.rate_limit" => {
+                self.error.context.as_ref()?.limit.map(Duration::from_secs)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the instance's duration limit when cobalt rejected the media for being
+    /// too long (`error.api.content.too_long`).
+    ///
+    /// Cobalt reports this limit in minutes, unlike the seconds of
+    /// [`Cobalt::duration_limit`](super::status::Cobalt::duration_limit). Only the limit
+    /// is reported, not the length of the rejected media.
+    pub fn duration_limit(&self) -> Option<Duration> {
+        match self.error.code.as_str() {
+            "error.api.content.too_long" => {
+                let minutes = self.error.context.as_ref()?.limit?;
+                Some(Duration::from_secs(minutes * 60))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ErrorResponse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "cobalt returned {}", self.error.code)
+    }
+}
+
+impl std::error::Error for ErrorResponse {}
+
+/// The broad kind of a media file, for deciding how to store or display it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MediaKind {
+    Video,
+    Audio,
+    Image,
+}
+
+impl MediaKind {
+    /// Classifies a MIME type such as `video/mp4`, ignoring any parameters.
+    pub fn from_content_type(content_type: &str) -> Option<MediaKind> {
+        match content_type.split('/').next()?.trim() {
+            "video" => Some(MediaKind::Video),
+            "audio" => Some(MediaKind::Audio),
+            "image" => Some(MediaKind::Image),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct MediaItem {
     pub r#type: String,
@@ -32,6 +100,78 @@ pub struct MediaItem {
     pub thumb: Option<String>,
 }
 
+/// The `type` of a picker item.
+///
+/// Types the crate doesn't know yet are kept in `Unknown`, so new cobalt versions don't
+/// break deserialization.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum MediaItemType {
+    Photo,
+    Video,
+    Gif,
+    Unknown(String),
+}
+
+impl MediaItemType {
+    pub fn as_str(&self) -> &str {
+        match self {
+            MediaItemType::Photo => "photo",
+            MediaItemType::Video => "video",
+            MediaItemType::Gif => "gif",
+            MediaItemType::Unknown(value) => value,
+        }
+    }
+
+    /// Returns the file extension items of this type are saved with, without the dot.
+    pub fn file_extension(&self) -> &'static str {
+        match self {
+            MediaItemType::Photo => "jpg",
+            MediaItemType::Gif => "gif",
+            MediaItemType::Video | MediaItemType::Unknown(_) => "mp4",
+        }
+    }
+}
+
+impl From<&str> for MediaItemType {
+    fn from(value: &str) -> Self {
+        match value {
+            "photo" => MediaItemType::Photo,
+            "video" => MediaItemType::Video,
+            "gif" => MediaItemType::Gif,
+            _ => MediaItemType::Unknown(value.to_string()),
+        }
+    }
+}
+
+impl From<String> for MediaItemType {
+    fn from(value: String) -> Self {
+        MediaItemType::from(value.as_str())
+    }
+}
+
+impl From<MediaItemType> for String {
+    fn from(value: MediaItemType) -> Self {
+        value.as_str().to_string()
+    }
+}
+
+impl MediaItem {
+    /// Returns the typed `type` of this picker item.
+    pub fn item_type(&self) -> MediaItemType {
+        MediaItemType::from(self.r#type.as_str())
+    }
+
+    /// Returns the kind of media this picker item holds, based on its `type`.
+    pub fn media_kind(&self) -> Option<MediaKind> {
+        match self.item_type() {
+            MediaItemType::Photo => Some(MediaKind::Image),
+            MediaItemType::Video | MediaItemType::Gif => Some(MediaKind::Video),
+            MediaItemType::Unknown(_) => None,
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct PickerResponse {
     pub status: String,
@@ -40,6 +180,80 @@ pub struct PickerResponse {
     pub picker: Vec<MediaItem>,
 }
 
+impl PickerResponse {
+    /// Returns the first item of the most preferred type, trying the types in
+    /// `preference` in order.
+    ///
+    /// Items whose type isn't listed are never chosen, so `None` is returned if no item
+    /// matches.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cobalt_tools::structs::media_response::{MediaItemType, PickerResponse};
+    ///
+    /// fn pick(picker: &PickerResponse) -> Option<&str> {
+    ///     picker
+    ///         .best_item(&[MediaItemType::Video, MediaItemType::Photo, MediaItemType::Gif])
+    ///         .map(|item| item.url.as_str())
+    /// }
+    /// ```
+    pub fn best_item(&self, preference: &[MediaItemType]) -> Option<&MediaItem> {
+        preference.iter().find_map(|item_type| {
+            self.picker
+                .iter()
+                .find(|item| item.item_type() == *item_type)
+        })
+    }
+
+    /// Returns every item with the path it is saved to in `dir`, without downloading
+    /// anything, e.g. to show what a picker download will write.
+    ///
+    /// Items are named like the files of
+    /// [`Client::download_picker_video_with_audio`](crate::api::client::Client::download_picker_video_with_audio):
+    /// the stem of `audio_filename`, falling back to `picker`, then the item's index and
+    /// the extension of its type, e.g. `sound_2.jpg`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cobalt_tools::structs::media_response::PickerResponse;
+    ///
+    /// fn print_plan(picker: &PickerResponse) {
+    ///     for (item, path) in picker.planned_files("downloads") {
+    ///         println!("{} -> {}", item.url, path.display());
+    ///     }
+    /// }
+    /// ```
+    pub fn planned_files(&self, dir: impl AsRef<Path>) -> Vec<(&MediaItem, PathBuf)> {
+        let dir = dir.as_ref();
+
+        self.picker
+            .iter()
+            .enumerate()
+            .map(|(index, item)| {
+                let name = format!(
+                    "{}.{}",
+                    self.item_base_name(index),
+                    item.item_type().file_extension()
+                );
+                (item, dir.join(name))
+            })
+            .collect()
+    }
+
+    /// Returns the file name, without extension, of the item at `index`.
+    pub(crate) fn item_base_name(&self, index: usize) -> String {
+        let stem = self
+            .audio_filename
+            .as_deref()
+            .map(Path::new)
+            .and_then(Path::file_stem)
+            .and_then(|stem| stem.to_str())
+            .unwrap_or("picker");
+
+        sanitize_filename(&format!("{}_{}", stem, index))
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct RedirectResponse {
     pub status: String,
@@ -63,4 +277,126 @@ impl Response {
             Response::Redirect(_) => Status::Redirect,
         }
     }
-}
\ No newline at end of file
+
+    /// Returns `true` if the response points at a single file to download, i.e. it is a
+    /// redirect or tunnel, and `false` for pickers and errors.
+    pub fn is_single_file(&self) -> bool {
+        matches!(self, Response::Redirect(_))
+    }
+
+    /// Turns an error response into an `Err`, passing pickers and redirects through.
+    ///
+    /// Use [`Response::into_media_result`] to handle errors reported in the body and
+    /// errors reported with an HTTP status the same way.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cobalt_tools::api::CobaltClient;
+    /// use cobalt_tools::structs::media_request::MediaRequestData;
+    ///
+    /// async fn fetch() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = CobaltClient.read().await;
+    ///
+    ///     let video_data = MediaRequestData {
+    ///         url: "https://www.youtube.com/watch?v=1lML-Uem6Ns",
+    ///         ..Default::default()
+    ///     };
+    ///
+    ///     let response = client.get_media(None, video_data).await?.into_result()?;
+    ///     println!("Response: {:#?}", response);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn into_result(self) -> Result<Response, ErrorResponse> {
+        match self {
+            Response::Error(error) => Err(error),
+            response => Ok(response),
+        }
+    }
+
+    /// Like [`Response::into_result`], but the error response becomes a
+    /// `MediaError::Cobalt`, so both ways cobalt reports errors end up in one `Err`.
+    ///
+    /// Cobalt usually answers errors with HTTP 400, which `get_media` returns as
+    /// `MediaError::Api`, but some errors come in the body of a 200 response, which
+    /// `get_media` returns as `Ok(Response::Error)`. After this, either is an `Err`, and
+    /// [`MediaError::error_response`] gives the error response for both.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cobalt_tools::api::client::Client;
+    /// use cobalt_tools::structs::media_request::MediaRequestData;
+    /// use cobalt_tools::structs::media_response::Response;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = Client::builder("your-api-key-here", "http://localhost:9000")
+    ///         .build()
+    ///         .expect("Failed to build client");
+    ///
+    ///     let video_data = MediaRequestData {
+    ///         url: "https://www.youtube.com/watch?v=1lML-Uem6Ns",
+    ///         ..Default::default()
+    ///     };
+    ///
+    ///     match client
+    ///         .get_media(None, video_data)
+    ///         .await
+    ///         .and_then(Response::into_media_result)
+    ///     {
+    ///         Ok(response) => println!("Response: {:#?}", response),
+    ///         Err(err) => match err.error_response() {
+    ///             Some(error) => eprintln!("cobalt rejected the request: {}", error.error.code),
+    ///             None => eprintln!("Request failed: {}", err),
+    ///         },
+    ///     }
+    /// }
+    /// ```
+    pub fn into_media_result(self) -> Result<Response, MediaError> {
+        self.into_result().map_err(MediaError::Cobalt)
+    }
+
+    /// Parses a response from the raw body returned by
+    /// [`Client::get_media_bytes`](crate::api::client::Client::get_media_bytes).
+    ///
+    /// This is what `get_media` does with the body. Callers with their own JSON parser
+    /// can deserialize the bytes themselves instead.
+    ///
+    /// # Errors
+    /// Returns `MediaError::EmptyResponse` for a blank body and
+    /// `MediaError::DeserializationError` if the body isn't a valid response.
+    pub fn from_bytes(body: &[u8]) -> Result<Response, MediaError> {
+        parse_body(None, body)
+    }
+}
+
+/// Parses a media response body, reporting common instance misconfigurations clearly.
+///
+/// An HTML page (e.g. a reverse proxy error or a web frontend at the API URI) yields
+/// `MediaError::UnexpectedContentType`, and a blank body yields `MediaError::EmptyResponse`,
+/// rather than an opaque serde error.
+pub(crate) fn parse_body<T: DeserializeOwned>(
+    content_type: Option<&str>,
+    body: &[u8],
+) -> Result<T, MediaError> {
+    check_content_type(content_type)?;
+
+    if body.iter().all(u8::is_ascii_whitespace) {
+        return Err(MediaError::EmptyResponse);
+    }
+
+    Ok(serde_json::from_slice(body)?)
+}
+
+/// Rejects HTML answers, which come from proxies or web frontends rather than the API.
+pub(crate) fn check_content_type(content_type: Option<&str>) -> Result<(), MediaError> {
+    if let Some(content_type) = content_type.filter(|value| value.starts_with("text/html")) {
+        return Err(MediaError::UnexpectedContentType(format!(
+            "the instance returned HTML ({}), not JSON",
+            content_type
+        )));
+    }
+
+    Ok(())
+}
@@ -1,6 +1,14 @@
+pub mod archive;
+pub mod client_init_error;
+pub mod download;
+pub mod filename;
+pub mod health;
 pub mod media_error;
 pub mod media_request;
 pub mod media_response;
+pub mod service;
+pub mod services;
+pub mod stats;
 pub mod status;
 
 pub use status::Response as StatusResponse;
\ No newline at end of file
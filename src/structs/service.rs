@@ -0,0 +1,64 @@
+/// Hosts served by each cobalt service, matched including subdomains.
+const SERVICE_HOSTS: &[(&str, &[&str])] = &[
+    ("bilibili", &["bilibili.com", "bilibili.tv", "b23.tv"]),
+    ("bluesky", &["bsky.app"]),
+    ("dailymotion", &["dailymotion.com", "dai.ly"]),
+    ("facebook", &["facebook.com", "fb.watch"]),
+    ("instagram", &["instagram.com", "ddinstagram.com"]),
+    ("loom", &["loom.com"]),
+    ("ok", &["ok.ru"]),
+    ("pinterest", &["pinterest.com", "pin.it"]),
+    ("reddit", &["reddit.com", "redd.it"]),
+    ("rutube", &["rutube.ru"]),
+    ("snapchat", &["snapchat.com"]),
+    ("soundcloud", &["soundcloud.com", "soundcloud.app.goo.gl"]),
+    ("streamable", &["streamable.com"]),
+    ("tiktok", &["tiktok.com"]),
+    ("tumblr", &["tumblr.com"]),
+    ("twitch", &["twitch.tv"]),
+    (
+        "twitter",
+        &["twitter.com", "x.com", "vxtwitter.com", "fixvx.com"],
+    ),
+    ("vimeo", &["vimeo.com"]),
+    ("vk", &["vk.com", "vkvideo.ru"]),
+    ("xiaohongshu", &["xiaohongshu.com", "xhslink.com"]),
+    ("youtube", &["youtube.com", "youtu.be"]),
+];
+
+/// Subdomains of a service host that serve the same pages, e.g. `www.youtube.com`.
+const PAGE_SUBDOMAINS: &[&str] = &["www", "m"];
+
+/// Returns the name cobalt uses for the service hosting `url`, e.g. `"youtube"`.
+///
+/// The names match the ones listed in `StatusResponse.cobalt.services`. Returns `None`
+/// for unparseable URLs and hosts the crate doesn't know about.
+pub fn service_for_url(url: &str) -> Option<&'static str> {
+    let url = reqwest::Url::parse(url).ok()?;
+    let host = url.host_str()?.to_ascii_lowercase();
+
+    SERVICE_HOSTS.iter().find_map(|(service, domains)| {
+        domains
+            .iter()
+            .any(|domain| host == *domain || host.ends_with(&format!(".{}", domain)))
+            .then_some(*service)
+    })
+}
+
+/// Like [`service_for_url`], but only matches the service hosts themselves and their
+/// `www.` and `m.` subdomains, where the service's pages are.
+///
+/// Other subdomains usually serve media files, e.g. `v.redd.it` or
+/// `va.media.tumblr.com`, so cobalt can't resolve links to them.
+pub fn page_service_for_url(url: &str) -> Option<&'static str> {
+    let url = reqwest::Url::parse(url).ok()?;
+    let host = url.host_str()?.to_ascii_lowercase();
+    let host = PAGE_SUBDOMAINS
+        .iter()
+        .find_map(|subdomain| host.strip_prefix(&format!("{}.", subdomain)))
+        .unwrap_or(&host);
+
+    SERVICE_HOSTS
+        .iter()
+        .find_map(|(service, domains)| domains.contains(&host).then_some(*service))
+}
@@ -0,0 +1,50 @@
+/// The services enabled on a cobalt instance, as returned by `Client::services`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Services(Vec<String>);
+
+impl Services {
+    /// Returns `true` if the instance lists `name`, e.g. `"youtube"`.
+    pub fn contains(&self, name: &str) -> bool {
+        self.0.iter().any(|service| service == name)
+    }
+
+    /// Returns the number of services.
+    pub fn count(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if no services are listed.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Iterates over the service names.
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.0.iter().map(String::as_str)
+    }
+
+    /// Returns the raw list of service names.
+    pub fn as_slice(&self) -> &[String] {
+        &self.0
+    }
+
+    /// Consumes the wrapper, returning the raw list of service names.
+    pub fn into_vec(self) -> Vec<String> {
+        self.0
+    }
+}
+
+impl From<Vec<String>> for Services {
+    fn from(services: Vec<String>) -> Self {
+        Services(services)
+    }
+}
+
+impl IntoIterator for Services {
+    type Item = String;
+    type IntoIter = std::vec::IntoIter<String>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
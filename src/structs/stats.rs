@@ -0,0 +1,12 @@
+use std::time::Duration;
+
+/// Timing and transfer size of a single operation, returned by the `*_timed` methods.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RequestStats {
+    /// Wall-clock time from the call until the result was ready.
+    pub elapsed: Duration,
+    /// Bytes of response body received.
+    pub bytes: u64,
+    /// The `X-Request-Id` sent with the request, if any.
+    pub request_id: Option<String>,
+}
@@ -1,12 +1,20 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
 
-#[derive(Deserialize, Debug)]
+use crate::api::builder::AuthScheme;
+
+use super::{
+    media_error::MediaError,
+    media_request::{DownloadMode, VideoQuality},
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Response {
     pub cobalt: Cobalt,
     pub git: Git,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct Cobalt {
     pub version: String,
@@ -14,11 +22,73 @@ pub struct Cobalt {
     pub start_time: String,
     pub duration_limit: u64,
     pub services: Vec<String>,
+    /// The Turnstile site key, present when the instance issues session tokens.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub turnstile_sitekey: Option<String>,
 }
 
-#[derive(Deserialize, Debug)]
+impl Cobalt {
+    /// Returns `duration_limit`, which cobalt reports in seconds, as a [`Duration`].
+    pub fn duration_limit(&self) -> Duration {
+        Duration::from_secs(self.duration_limit)
+    }
+
+    /// Checks `duration` against the instance's duration limit.
+    ///
+    /// # Errors
+    /// Returns `MediaError::TooLong` if `duration` exceeds the limit.
+    pub fn check_duration(&self, duration: Duration) -> Result<(), MediaError> {
+        let limit = self.duration_limit();
+
+        if duration > limit {
+            return Err(MediaError::TooLong { duration, limit });
+        }
+
+        Ok(())
+    }
+
+    /// Picks the `Authorization` scheme to use with this instance.
+    ///
+    /// The status only reveals whether the instance issues session tokens, which are
+    /// sent as `Bearer`. Without them only API keys can work, so [`AuthScheme::ApiKey`]
+    /// is returned. With them the instance may accept either, and `configured` is kept.
+    pub fn auth_scheme(&self, configured: AuthScheme) -> AuthScheme {
+        match self.turnstile_sitekey {
+            Some(_) => configured,
+            None => AuthScheme::ApiKey,
+        }
+    }
+
+    /// Returns the video qualities that can be requested.
+    ///
+    /// The status endpoint doesn't report per-instance capabilities, so this is the full
+    /// list the crate knows cobalt to accept. Services may deliver a lower quality when
+    /// the requested one isn't available.
+    pub fn supported_qualities(&self) -> &'static [VideoQuality] {
+        VideoQuality::ALL
+    }
+
+    /// Returns the download modes that can be requested. Like
+    /// [`Cobalt::supported_qualities`], this is the crate's static list.
+    pub fn supported_download_modes(&self) -> &'static [DownloadMode] {
+        DownloadMode::ALL
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Git {
     pub branch: String,
     pub commit: String,
     pub remote: String,
-}
\ No newline at end of file
+}
+/// The part of a status response [`Client::services_only`](crate::api::client::Client::services_only)
+/// reads; every other field is skipped while parsing.
+#[derive(Deserialize, Debug)]
+pub(crate) struct ServicesOnly {
+    pub cobalt: CobaltServices,
+}
+
+#[derive(Deserialize, Debug)]
+pub(crate) struct CobaltServices {
+    pub services: Vec<String>,
+}